@@ -31,11 +31,17 @@ fn main() {
     // Add performance UI
     .add_plugins(bevy::diagnostic::FrameTimeDiagnosticsPlugin::default())
     .add_plugins(PerfUiPlugin)
+    // Log the grid's diagnostics (entities per cell, events, update time) every second
+    .add_plugins(bevy::diagnostic::LogDiagnosticsPlugin::default())
     .add_plugins(
         // Add grid plugin. `Marker` is a marker component for opting entities into the grid.
         // Our const `N` sets pre-allocated capacity of 8 for each grid cell. Default is 4.
         UniformGrid2dPlugin::<Marker, N>::default()
             .debug(true)
+            // Shade occupied cells by how close they are to the capacity `N`
+            .heatmap(true)
+            // Register diagnostics to help pick a good `N` for the workload
+            .diagnostics(true)
             // The grid shape is defined using the plugin's builder methods.
             .dimensions(UVec2::splat(30))
             .spacing(Vec2::splat(20.)),
@@ -126,7 +132,10 @@ fn movement(
 // Update the sprite's color whenever it enters or leaves the grid,
 // as well as whenever it moves to a new grid cell
 fn update_color(mut sprites: Query<&mut Sprite>, mut events: EventReader<GridEvent>) {
-    for &GridEvent { entity, operation } in events.read() {
+    for &GridEvent {
+        entity, operation, ..
+    } in events.read()
+    {
         let Ok(mut sprite) = sprites.get_mut(entity) else {
             continue;
         };
@@ -145,6 +154,7 @@ fn update_color(mut sprites: Query<&mut Sprite>, mut events: EventReader<GridEve
             GridOperation::Remove { .. } => {
                 sprite.color = OUT;
             }
+            GridOperation::Floor { .. } => {}
         }
     }
 }
@@ -0,0 +1,232 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    ecs::{
+        component::Component,
+        entity::{Entity, EntityHashMap},
+        system::Command,
+        world::{Mut, World},
+    },
+    math::UVec2,
+};
+
+use crate::{
+    component::{GridCell, GridLayers},
+    resource::Grid,
+    snapshot::{GridDelta, GridSnapshot},
+};
+
+/// Command that replaces the configuration and contents of a grid with a
+/// `GridSnapshot`, and inserts, updates or removes the `GridCell` components of
+/// the affected entities to match. No `GridEvent`s are written.
+pub struct RestoreGrid<Marker: Component, const N: usize = 4> {
+    /// Entity with the `Grid<Marker, N>` component to restore, or `None` for the
+    /// `Grid<Marker, N>` resource.
+    pub grid: Option<Entity>,
+    pub snapshot: GridSnapshot,
+    marker: PhantomData<Marker>,
+}
+
+impl<Marker: Component, const N: usize> RestoreGrid<Marker, N> {
+    pub fn new(grid: Option<Entity>, snapshot: GridSnapshot) -> Self {
+        Self {
+            grid,
+            snapshot,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<Marker: Component, const N: usize> Command for RestoreGrid<Marker, N> {
+    fn apply(self, world: &mut World) {
+        let Some(mut grid) = grid_mut::<Marker, N>(world, self.grid) else {
+            return;
+        };
+        let mut moved: EntityHashMap<_> = grid.iter().map(|entity| (entity, None)).collect();
+        grid.restore(&self.snapshot);
+        let cells: Vec<_> = grid.iter_occupancy().map(|(cell, _)| cell).collect();
+        for cell in cells {
+            let layers = grid.get_with_layers(cell);
+            for ((entity, floor), (_, layers)) in grid.get_with_floors(cell).zip(layers) {
+                moved.insert(entity, Some((cell, floor, layers)));
+            }
+        }
+        sync_grid_cells::<Marker, N>(world, self.grid, moved);
+    }
+}
+
+/// Command that rewinds a grid with `Grid::rewind`, one delta at a time from
+/// newest to oldest, and inserts, updates or removes the `GridCell` components
+/// of the affected entities to match. No `GridEvent`s are written. Does nothing
+/// to grids with a `chunk_size`, which can't be rewound.
+pub struct RewindGrid<Marker: Component, const N: usize = 4> {
+    /// Entity with the `Grid<Marker, N>` component to rewind, or `None` for the
+    /// `Grid<Marker, N>` resource.
+    pub grid: Option<Entity>,
+    /// Deltas returned by `Grid::checkpoint`, newest first.
+    pub deltas: Vec<GridDelta>,
+    marker: PhantomData<Marker>,
+}
+
+impl<Marker: Component, const N: usize> RewindGrid<Marker, N> {
+    pub fn new(grid: Option<Entity>, deltas: Vec<GridDelta>) -> Self {
+        Self {
+            grid,
+            deltas,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<Marker: Component, const N: usize> Command for RewindGrid<Marker, N> {
+    fn apply(self, world: &mut World) {
+        let Some(mut grid) = grid_mut::<Marker, N>(world, self.grid) else {
+            return;
+        };
+        // Also undoes the changes since the last checkpoint when there are no deltas
+        let mut moved = grid.rewind(&GridDelta::default());
+        for delta in &self.deltas {
+            moved.extend(grid.rewind(delta));
+        }
+        sync_grid_cells::<Marker, N>(world, self.grid, moved);
+    }
+}
+
+fn grid_mut<Marker: Component, const N: usize>(
+    world: &mut World,
+    grid: Option<Entity>,
+) -> Option<Mut<'_, Grid<Marker, N>>> {
+    match grid {
+        Some(grid) => world.get_mut::<Grid<Marker, N>>(grid),
+        None => world.get_resource_mut::<Grid<Marker, N>>(),
+    }
+}
+
+/// Make the `GridCell` components of the `moved` entities match their new cell,
+/// floor and layers in `grid`, or remove them if they are no longer in a cell.
+fn sync_grid_cells<Marker: Component, const N: usize>(
+    world: &mut World,
+    grid: Option<Entity>,
+    moved: EntityHashMap<Option<(UVec2, i32, GridLayers)>>,
+) {
+    for (entity, cell) in moved {
+        let Ok(mut entity) = world.get_entity_mut(entity) else {
+            continue;
+        };
+        match cell {
+            Some((cell, floor, layers)) => match entity.get_mut::<GridCell<Marker, N>>() {
+                Some(mut current_cell) if current_cell.grid() == grid => {
+                    current_cell.inner = cell;
+                    current_cell.floor = floor;
+                    current_cell.layers = layers;
+                }
+                _ => {
+                    entity.insert(GridCell::<Marker, N>::new(cell, grid, floor, layers));
+                }
+            },
+            // Leave the cells of entities that moved to another grid alone
+            None => {
+                if entity
+                    .get::<GridCell<Marker, N>>()
+                    .is_some_and(|current_cell| current_cell.grid() == grid)
+                {
+                    entity.remove::<GridCell<Marker, N>>();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Component)]
+    struct TestMarker;
+
+    /// A world with a journaling grid, and two entities in it with their
+    /// `GridCell`s, and one outside of it.
+    fn world() -> (World, [Entity; 3]) {
+        let mut world = World::new();
+        let entities = [(); 3].map(|_| world.spawn_empty().id());
+        world.insert_resource(
+            Grid::<TestMarker>::default()
+                .with_dimensions(UVec2::new(10, 10))
+                .with_journal(true),
+        );
+        place(&mut world, entities[0], UVec2::new(1, 1));
+        place(&mut world, entities[1], UVec2::new(2, 2));
+        (world, entities)
+    }
+
+    /// Move an `entity` to `cell`, keeping its `GridCell` in sync like `update_grid`.
+    fn place(world: &mut World, entity: Entity, cell: UVec2) {
+        let current_cell = world
+            .get::<GridCell<TestMarker>>(entity)
+            .map(|current_cell| current_cell.inner);
+        let mut grid = world.resource_mut::<Grid<TestMarker>>();
+        match current_cell {
+            Some(current_cell) => grid.update(entity, current_cell, cell).unwrap(),
+            None => grid.insert(entity, cell).unwrap(),
+        }
+        world.entity_mut(entity).insert(GridCell::<TestMarker>::new(
+            cell,
+            None,
+            0,
+            GridLayers::DEFAULT,
+        ));
+    }
+
+    /// Move the first entity, remove the second and add the third.
+    fn change(world: &mut World, [first, second, third]: [Entity; 3]) {
+        place(world, first, UVec2::new(3, 3));
+        world
+            .resource_mut::<Grid<TestMarker>>()
+            .remove(second, UVec2::new(2, 2))
+            .unwrap();
+        world.entity_mut(second).remove::<GridCell<TestMarker>>();
+        place(world, third, UVec2::new(4, 4));
+    }
+
+    fn cells(world: &World, entities: [Entity; 3]) -> [Option<UVec2>; 3] {
+        entities.map(|entity| {
+            world
+                .get::<GridCell<TestMarker>>(entity)
+                .map(|cell| cell.inner)
+        })
+    }
+
+    #[test]
+    fn test_restore_grid() {
+        let (mut world, entities) = world();
+        let snapshot = world.resource::<Grid<TestMarker>>().snapshot();
+        change(&mut world, entities);
+
+        RestoreGrid::<TestMarker>::new(None, snapshot.clone()).apply(&mut world);
+
+        // The first `GridCell` is updated, the second inserted and the third removed
+        let expected = [Some(UVec2::new(1, 1)), Some(UVec2::new(2, 2)), None];
+        assert_eq!(cells(&world, entities), expected);
+        assert_eq!(world.resource::<Grid<TestMarker>>().snapshot(), snapshot);
+    }
+
+    #[test]
+    fn test_rewind_grid() {
+        let (mut world, entities) = world();
+        world.resource_mut::<Grid<TestMarker>>().checkpoint();
+        let snapshot = world.resource::<Grid<TestMarker>>().snapshot();
+        change(&mut world, entities);
+        let delta = world.resource_mut::<Grid<TestMarker>>().checkpoint();
+
+        RewindGrid::<TestMarker>::new(None, vec![delta]).apply(&mut world);
+
+        let expected = [Some(UVec2::new(1, 1)), Some(UVec2::new(2, 2)), None];
+        assert_eq!(cells(&world, entities), expected);
+        assert_eq!(world.resource::<Grid<TestMarker>>().snapshot(), snapshot);
+
+        // Without deltas, only the changes since the last checkpoint are undone
+        change(&mut world, entities);
+        RewindGrid::<TestMarker>::new(None, Vec::new()).apply(&mut world);
+        assert_eq!(cells(&world, entities), expected);
+    }
+}
@@ -0,0 +1,18 @@
+use bevy::{ecs::component::Component, prelude::ReflectComponent, reflect::Reflect};
+
+/// Keeps the chunks around an entity loaded in every grid with a `chunk_size`,
+/// e.g. a player or a camera. Uses the same position component as the grid's
+/// entities, but doesn't need to be in the grid itself.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChunkAnchor {
+    /// Number of chunks loaded out from the anchor's own chunk, along either axis.
+    pub radius: u32,
+}
+
+impl ChunkAnchor {
+    pub fn new(radius: u32) -> Self {
+        Self { radius }
+    }
+}
@@ -1,20 +1,67 @@
 use std::marker::PhantomData;
 
-use bevy::{ecs::component::Component, math::UVec2};
+use bevy::{
+    ecs::{component::Component, entity::Entity},
+    math::UVec2,
+    prelude::ReflectComponent,
+    reflect::Reflect,
+};
 
-#[derive(Component, Debug, Default)]
+use crate::{component::GridLayers, type_path::impl_marker_type_path};
+
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component, type_path = false)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub struct GridCell<Marker: Component, const N: usize = 4> {
     pub inner: UVec2,
+    /// Entity with the `Grid<Marker, N>` component the cell belongs to, or `None`
+    /// for the `Grid<Marker, N>` resource.
+    #[entities]
+    grid: Option<Entity>,
+    /// Floor the entity is on, see `FloorMode`.
+    pub(crate) floor: i32,
+    /// Layers the entity is on, see `GridLayers`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) layers: GridLayers,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[reflect(ignore)]
     marker: PhantomData<Marker>,
 }
 
+impl_marker_type_path!(GridCell<Marker, N>);
+
 impl<Marker: Component, const N: usize> GridCell<Marker, N> {
-    pub(crate) fn new(inner: UVec2) -> Self {
+    pub(crate) fn new(inner: UVec2, grid: Option<Entity>, floor: i32, layers: GridLayers) -> Self {
         Self {
             inner,
+            grid,
+            floor,
+            layers,
             marker: PhantomData,
         }
     }
+
+    /// Getter method for the cell's `grid`.
+    #[inline]
+    pub fn grid(&self) -> Option<Entity> {
+        self.grid
+    }
+
+    /// Getter method for the cell's `floor`.
+    #[inline]
+    pub fn floor(&self) -> i32 {
+        self.floor
+    }
+
+    /// Getter method for the cell's `layers`.
+    #[inline]
+    pub fn layers(&self) -> GridLayers {
+        self.layers
+    }
 }
 
 impl<Marker: Component, const N: usize> std::ops::Deref for GridCell<Marker, N> {
@@ -0,0 +1,8 @@
+use bevy::{ecs::component::Component, prelude::ReflectComponent, reflect::Reflect};
+
+/// Floor of an entity in grids using `FloorMode::Explicit`. Removing it moves
+/// the entity to floor `0` the next time its position changes.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GridFloor(pub i32);
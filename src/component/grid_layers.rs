@@ -0,0 +1,50 @@
+use bevy::{ecs::component::Component, prelude::ReflectComponent, reflect::Reflect};
+
+/// Layer bitmask of an entity, e.g. allies, enemies and pickups. It is stored
+/// next to the entity in its grid cell, so queries like `Grid::iter_radius_in_layers`
+/// can filter entities without looking them up. Entities without one are on
+/// `GridLayers::DEFAULT`; removing it moves the entity back there the next time
+/// its position changes. Changes to the layers of static entities are ignored.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Component)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GridLayers(pub u32);
+
+impl GridLayers {
+    /// Only the first layer.
+    pub const DEFAULT: Self = Self(1);
+    /// Every layer.
+    pub const ALL: Self = Self(u32::MAX);
+    /// No layers, hidden from every layer-filtered query.
+    pub const NONE: Self = Self(0);
+
+    /// Layers with only the `layer`th bit set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `layer` is 32 or more, as there are only 32 layers.
+    #[inline]
+    pub const fn layer(layer: u32) -> Self {
+        assert!(layer < u32::BITS, "GridLayers only has 32 layers");
+        Self(1 << layer)
+    }
+
+    /// Whether the two masks share a layer.
+    #[inline]
+    pub const fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl Default for GridLayers {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl std::ops::BitOr for GridLayers {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
@@ -0,0 +1,18 @@
+use bevy::{ecs::component::Component, prelude::ReflectComponent, reflect::Reflect};
+
+/// Makes an entity observe the entities around its `GridCell` in every grid with
+/// an `InterestMap`, e.g. the avatar of a client the server replicates to. The
+/// observer has to be in the grid itself. See `InterestEnter` and `InterestExit`.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GridObserver {
+    /// Number of cells the observer sees out from its own cell, along either axis.
+    pub radius: u32,
+}
+
+impl GridObserver {
+    pub fn new(radius: u32) -> Self {
+        Self { radius }
+    }
+}
@@ -0,0 +1,14 @@
+use bevy::{ecs::component::Component, prelude::ReflectComponent, reflect::Reflect};
+
+/// Marks an entity that never moves, like a tree or a wall, so the `Grid`
+/// resource indexes it once in a compact sorted layout instead of tracking its
+/// position every frame. Queries include static entities unless they ask for
+/// `EntityKind::Dynamic`.
+///
+/// Changes to the position of a static entity are ignored; remove this component
+/// to move it. Static entities outside the grid are not indexed, whatever the
+/// `OutOfBoundsPolicy`. Members of component grids ignore this marker.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GridStatic;
@@ -0,0 +1,38 @@
+use std::marker::PhantomData;
+
+use bevy::{ecs::component::Component, math::IVec2, prelude::ReflectComponent, reflect::Reflect};
+
+use crate::type_path::impl_marker_type_path;
+
+/// Axial coordinate `(q, r)` of the hex an entity is in, in the `HexGrid<Marker, N>`.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component, type_path = false)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
+pub struct HexCell<Marker: Component, const N: usize = 4> {
+    pub inner: IVec2,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[reflect(ignore)]
+    marker: PhantomData<Marker>,
+}
+
+impl_marker_type_path!(HexCell<Marker, N>);
+
+impl<Marker: Component, const N: usize> HexCell<Marker, N> {
+    pub(crate) fn new(inner: IVec2) -> Self {
+        Self {
+            inner,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<Marker: Component, const N: usize> std::ops::Deref for HexCell<Marker, N> {
+    type Target = IVec2;
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
@@ -0,0 +1,22 @@
+use bevy::{
+    ecs::{component::Component, entity::Entity},
+    prelude::ReflectComponent,
+    reflect::Reflect,
+};
+
+/// Makes an entity a member of the `Grid<Marker, N>` component on the target
+/// entity instead of the `Grid<Marker, N>` resource. The entity still needs the
+/// grid's `Marker` component. Its position is relative to the target entity, so
+/// members are usually its children. Once `InGrid` is removed, or the target entity
+/// is despawned, the entity leaves the grid and joins the `Grid<Marker, N>` resource.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+#[relationship(relationship_target = GridMembers)]
+pub struct InGrid(pub Entity);
+
+/// Every entity that is a member of the grid on this entity. Kept in sync
+/// with `InGrid` automatically.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+#[relationship_target(relationship = InGrid)]
+pub struct GridMembers(Vec<Entity>);
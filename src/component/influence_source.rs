@@ -0,0 +1,24 @@
+use bevy::{ecs::component::Component, prelude::ReflectComponent, reflect::Reflect};
+
+/// Makes an entity contribute influence to its `GridCell` and the cells around
+/// it in every grid with an `InfluenceMap`.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component)]
+pub struct InfluenceSource {
+    /// Channel (e.g. team) the influence is added to.
+    pub channel: u32,
+    /// Influence added to the entity's own cell. May be negative.
+    pub weight: f32,
+    /// Number of cells the influence spreads out from the entity's own cell.
+    pub radius: u32,
+}
+
+impl InfluenceSource {
+    pub fn new(channel: u32, weight: f32, radius: u32) -> Self {
+        Self {
+            channel,
+            weight,
+            radius,
+        }
+    }
+}
@@ -1,3 +1,19 @@
+mod chunk_anchor;
 mod grid_cell;
+mod grid_floor;
+mod grid_layers;
+mod grid_observer;
+mod grid_static;
+mod hex_cell;
+mod in_grid;
+mod influence_source;
 
+pub use chunk_anchor::*;
 pub use grid_cell::*;
+pub use grid_floor::*;
+pub use grid_layers::*;
+pub use grid_observer::*;
+pub use grid_static::*;
+pub use hex_cell::*;
+pub use in_grid::*;
+pub use influence_source::*;
@@ -1,15 +1,22 @@
 use bevy::{
     ecs::entity::Entity,
-    math::{IVec2, UVec2},
+    math::{IVec2, UVec2, Vec3},
 };
 use thiserror::Error;
 
 #[derive(Error, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GridError {
     #[error("cell {0} is outside the grid")]
     OutOfBounds(IVec2),
     #[error("cell {0} not found")]
     CellNotFound(UVec2),
+    #[error("hex {0} not found")]
+    HexNotFound(IVec2),
     #[error("entity {0:?} not found")]
     EntityNotFound(Entity),
+    #[error("chunk {0} is not loaded")]
+    ChunkNotLoaded(UVec2),
+    #[error("translation {0} is not finite")]
+    NonFinite(Vec3),
 }
@@ -0,0 +1,59 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    ecs::{component::Component, event::Event},
+    math::UVec2,
+    reflect::Reflect,
+};
+
+use crate::type_path::impl_marker_type_path;
+
+/// A chunk of the `Grid<Marker, N>` resource was loaded or unloaded. See
+/// `ChunkAnchor`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Event, Reflect)]
+#[reflect(type_path = false)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
+pub struct ChunkEvent<Marker: Component, const N: usize = 4> {
+    pub chunk: UVec2,
+    pub operation: ChunkOperation,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[reflect(ignore)]
+    marker: PhantomData<Marker>,
+}
+
+impl_marker_type_path!(ChunkEvent<Marker, N>);
+
+impl<Marker: Component, const N: usize> ChunkEvent<Marker, N> {
+    pub(crate) fn new(chunk: UVec2, operation: ChunkOperation) -> Self {
+        Self {
+            chunk,
+            operation,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<Marker: Component, const N: usize> std::fmt::Display for ChunkEvent<Marker, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ChunkEvent {{ chunk=({0}, {1}) operation={2:?} }}",
+            self.chunk.x, self.chunk.y, self.operation
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChunkOperation {
+    /// The chunk came within range of a `ChunkAnchor`. Its entities are inserted
+    /// into the grid in the same frame.
+    Activate,
+    /// The chunk is no longer in range of any `ChunkAnchor`. Its entities are
+    /// removed from the grid in the same frame.
+    Deactivate,
+}
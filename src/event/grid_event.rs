@@ -1,29 +1,55 @@
 use bevy::{
     ecs::{entity::Entity, event::Event},
     math::UVec2,
+    reflect::Reflect,
 };
 
-#[derive(Clone, Copy, Debug, Event)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Event, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GridEvent {
     pub entity: Entity,
+    /// Entity with the `Grid` component the event happened in, or `None` for a
+    /// `Grid` resource.
+    pub grid: Option<Entity>,
     pub operation: GridOperation,
 }
 
 impl std::fmt::Display for GridEvent {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "GridEvent {{ entity={0} operation={1} }}",
-            self.entity, self.operation
-        )
+        match self.grid {
+            Some(grid) => write!(
+                f,
+                "GridEvent {{ entity={0} grid={1} operation={2} }}",
+                self.entity, grid, self.operation
+            ),
+            None => write!(
+                f,
+                "GridEvent {{ entity={0} operation={1} }}",
+                self.entity, self.operation
+            ),
+        }
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GridOperation {
-    Insert { to: UVec2 },
-    Remove { from: UVec2 },
-    Update { from: UVec2, to: UVec2 },
+    Insert {
+        to: UVec2,
+    },
+    Remove {
+        from: UVec2,
+    },
+    Update {
+        from: UVec2,
+        to: UVec2,
+    },
+    /// The entity moved to another floor. Written after the `Update` event
+    /// if it changed cells as well.
+    Floor {
+        from: i32,
+        to: i32,
+    },
 }
 
 impl std::fmt::Display for GridOperation {
@@ -37,6 +63,7 @@ impl std::fmt::Display for GridOperation {
                 "Update {{ ({}, {}) -> ({}, {}) }}",
                 from.x, from.y, to.x, to.y
             ),
+            Floor { from, to } => write!(f, "Floor {{ {from} -> {to} }}"),
         }
     }
 }
@@ -0,0 +1,45 @@
+use bevy::{
+    ecs::{entity::Entity, event::Event},
+    math::IVec2,
+    reflect::Reflect,
+};
+
+#[derive(Clone, Copy, Debug, Event, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HexGridEvent {
+    pub entity: Entity,
+    pub operation: HexGridOperation,
+}
+
+impl std::fmt::Display for HexGridEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "HexGridEvent {{ entity={0} operation={1} }}",
+            self.entity, self.operation
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HexGridOperation {
+    Insert { to: IVec2 },
+    Remove { from: IVec2 },
+    Update { from: IVec2, to: IVec2 },
+}
+
+impl std::fmt::Display for HexGridOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use HexGridOperation::*;
+        match self {
+            Insert { to } => write!(f, "Insert {{ none -> ({}, {}) }}", to.x, to.y),
+            Remove { from } => write!(f, "Remove {{ ({}, {}) -> none }}", from.x, from.y),
+            Update { from, to } => write!(
+                f,
+                "Update {{ ({}, {}) -> ({}, {}) }}",
+                from.x, from.y, to.x, to.y
+            ),
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use bevy::{
+    ecs::{entity::Entity, event::Event},
+    reflect::Reflect,
+};
+
+/// An `entity` came into view of a `GridObserver`. See `InterestMap`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Event, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InterestEnter {
+    pub observer: Entity,
+    pub entity: Entity,
+}
+
+/// An `entity` went out of view of a `GridObserver`, either because one of them
+/// moved or the entity left the grid or was despawned. See `InterestMap`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Event, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InterestExit {
+    pub observer: Entity,
+    pub entity: Entity,
+}
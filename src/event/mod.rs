@@ -1,5 +1,11 @@
+mod chunk_event;
 mod grid_event;
+mod hex_grid_event;
+mod interest_event;
 mod transform_grid_event;
 
+pub use chunk_event::*;
 pub use grid_event::*;
+pub use hex_grid_event::*;
+pub use interest_event::*;
 pub use transform_grid_event::*;
@@ -1,11 +1,15 @@
 use std::marker::PhantomData;
 
 use bevy::{
-    ecs::{component::Component, event::Event},
-    math::{UVec2, Vec2},
+    ecs::{component::Component, entity::Entity, event::Event},
+    math::{Affine2, UVec2, Vec2},
+    reflect::Reflect,
 };
 
-#[derive(Clone, Copy, Debug, Event)]
+use crate::type_path::impl_marker_type_path;
+
+#[derive(Clone, Copy, Debug, Event, Reflect)]
+#[reflect(type_path = false)]
 pub struct TransformGridEvent<Marker: Component, const N: usize = 4> {
     /// Shape of the grid in cell units.
     pub(crate) dimensions: Option<UVec2>,
@@ -13,9 +17,17 @@ pub struct TransformGridEvent<Marker: Component, const N: usize = 4> {
     pub(crate) spacing: Option<Vec2>,
     /// Point in world space to anchor the grid. Defaults to the origin.
     pub(crate) anchor: Option<Vec2>,
+    /// Rotation, shear and scale applied to the grid.
+    pub(crate) transform: Option<Affine2>,
+    /// Entity with the `Grid` component to transform. Transforms the `Grid`
+    /// resource when `None`.
+    pub(crate) grid: Option<Entity>,
+    #[reflect(ignore)]
     marker: PhantomData<Marker>,
 }
 
+impl_marker_type_path!(TransformGridEvent<Marker, N>);
+
 impl<Marker: Component, const N: usize> TransformGridEvent<Marker, N> {
     /// Getter method for the grid's `dimensions`.
     pub fn dimensions(&self) -> Option<UVec2> {
@@ -32,6 +44,16 @@ impl<Marker: Component, const N: usize> TransformGridEvent<Marker, N> {
         self.anchor
     }
 
+    /// Getter method for the grid's `transform`.
+    pub fn transform(&self) -> Option<Affine2> {
+        self.transform
+    }
+
+    /// Getter method for the event's target `grid`.
+    pub fn grid(&self) -> Option<Entity> {
+        self.grid
+    }
+
     /// Builder method to set the grid's `dimensions`.
     pub fn with_dimensions(mut self, value: impl Into<UVec2>) -> Self {
         self.dimensions = Some(value.into());
@@ -49,6 +71,19 @@ impl<Marker: Component, const N: usize> TransformGridEvent<Marker, N> {
         self.anchor = Some(value.into());
         self
     }
+
+    /// Builder method to set the grid's `transform`.
+    pub fn with_transform(mut self, value: Affine2) -> Self {
+        self.transform = Some(value);
+        self
+    }
+
+    /// Builder method to transform the `Grid` component on `entity` instead
+    /// of the `Grid` resource.
+    pub fn with_grid(mut self, entity: Entity) -> Self {
+        self.grid = Some(entity);
+        self
+    }
 }
 
 impl<Marker: Component, const N: usize> Default for TransformGridEvent<Marker, N> {
@@ -57,6 +92,8 @@ impl<Marker: Component, const N: usize> Default for TransformGridEvent<Marker, N
             dimensions: Some(UVec2::ONE),
             spacing: Some(Vec2::ONE),
             anchor: Some(Vec2::ZERO),
+            transform: None,
+            grid: None,
             marker: PhantomData,
         }
     }
@@ -0,0 +1,42 @@
+use std::marker::PhantomData;
+
+use bevy::{ecs::component::Component, gizmos::config::GizmoConfigGroup, reflect::Reflect};
+
+use crate::type_path::impl_marker_type_path;
+
+/// Gizmo config group for the debug drawing of a `Grid<Marker, N>`. Its
+/// `GizmoConfig` is kept in sync with the grid's `GridDebugSettings`.
+#[derive(GizmoConfigGroup, Reflect)]
+#[reflect(type_path = false)]
+pub struct GridGizmos<Marker: Component> {
+    #[reflect(ignore)]
+    marker: PhantomData<Marker>,
+}
+
+impl<Marker: Component> Default for GridGizmos<Marker> {
+    fn default() -> Self {
+        Self {
+            marker: PhantomData,
+        }
+    }
+}
+
+impl_marker_type_path!(GridGizmos<Marker>);
+
+/// Gizmo config group for the debug drawing of a `HexGrid<Marker, N>`.
+#[derive(GizmoConfigGroup, Reflect)]
+#[reflect(type_path = false)]
+pub struct HexGridGizmos<Marker: Component> {
+    #[reflect(ignore)]
+    marker: PhantomData<Marker>,
+}
+
+impl<Marker: Component> Default for HexGridGizmos<Marker> {
+    fn default() -> Self {
+        Self {
+            marker: PhantomData,
+        }
+    }
+}
+
+impl_marker_type_path!(HexGridGizmos<Marker>);
@@ -1,8 +1,16 @@
 #![allow(clippy::type_complexity)]
+pub mod command;
 pub mod component;
 pub mod error;
 pub mod event;
+pub mod gizmos;
 pub mod plugin;
+pub mod position;
 pub mod prelude;
+pub mod query;
 pub mod resource;
+pub mod snapshot;
 pub mod system;
+#[cfg(test)]
+mod test_util;
+mod type_path;
@@ -1,32 +1,97 @@
 use std::marker::PhantomData;
 
 use bevy::{
-    app::{Plugin, Update},
-    ecs::component::Component,
-    math::{UVec2, Vec2},
+    app::{Plugin, PostUpdate, Update},
+    diagnostic::{Diagnostic, RegisterDiagnostic},
+    ecs::{component::Component, schedule::IntoScheduleConfigs},
+    gizmos::{AppGizmoBuilder, GizmoPlugin},
+    math::{Affine2, UVec2, Vec2},
+    transform::components::Transform,
 };
 
 use crate::{
-    event::{GridEvent, TransformGridEvent},
-    resource::Grid,
-    system::{update_debug_grid_lines, update_grid},
+    component::{
+        ChunkAnchor, GridCell, GridFloor, GridLayers, GridMembers, GridObserver, GridStatic,
+        HexCell, InGrid, InfluenceSource,
+    },
+    event::{
+        ChunkEvent, ChunkOperation, GridEvent, GridOperation, HexGridEvent, HexGridOperation,
+        InterestEnter, InterestExit, TransformGridEvent,
+    },
+    gizmos::{GridGizmos, HexGridGizmos},
+    position::GridPosition,
+    resource::{
+        FloorMode, Grid, GridDebugSettings, GridDiagnostics, GridHighlights, GridPlane,
+        GridProjection, HexGrid, HexLayout, InfluenceFalloff, InfluenceMap, InterestMap,
+        OutOfBoundsPolicy,
+    },
+    system::{
+        clear_grid_highlights, sync_debug_gizmo_config, update_debug_entity_links,
+        update_debug_grid_lines, update_debug_heatmap, update_debug_hex_grid,
+        update_debug_highlights, update_grid, update_grid_chunks, update_grid_components,
+        update_grid_diagnostics, update_hex_grid, update_influence_map, update_interest_map,
+    },
 };
 
-pub struct UniformGrid2dPlugin<Marker: Component, const N: usize = 4> {
+#[cfg(feature = "debug_labels")]
+use crate::system::update_debug_heatmap_labels;
+#[cfg(feature = "render")]
+use crate::{
+    resource::{CameraViews, GridCulling},
+    system::{update_camera_views, update_grid_culling},
+};
+#[cfg(feature = "render")]
+use bevy::app::First;
+
+/// Indexes every entity with the `Marker` component in a `Grid<Marker, N>`, using
+/// the position supplied by its `P` component. See `GridPosition`.
+pub struct UniformGrid2dPlugin<Marker: Component, const N: usize = 4, P: GridPosition = Transform> {
     dimensions: UVec2,
     spacing: Vec2,
     anchor: Vec2,
+    projection: GridProjection,
+    plane: GridPlane,
+    transform: Affine2,
+    hysteresis: f32,
+    out_of_bounds_policy: OutOfBoundsPolicy,
+    floor_mode: FloorMode,
+    deterministic: bool,
+    journal: bool,
+    chunk_size: Option<u32>,
     debug: bool,
-    marker: PhantomData<Marker>,
+    heatmap: bool,
+    heatmap_labels: bool,
+    influence: Option<(InfluenceFalloff, f32)>,
+    interest: bool,
+    #[cfg(feature = "render")]
+    culling: bool,
+    diagnostics: bool,
+    marker: PhantomData<(Marker, P)>,
 }
 
-impl<Marker: Component, const N: usize> UniformGrid2dPlugin<Marker, N> {
-    /// Builder method to enable debug mode.
+impl<Marker: Component, const N: usize, P: GridPosition> UniformGrid2dPlugin<Marker, N, P> {
+    /// Builder method to enable debug mode. Debug drawing can also be toggled and
+    /// styled at runtime through the grid's `GridDebugSettings` resource.
     pub fn debug(mut self, value: bool) -> Self {
         self.debug = value;
         self
     }
 
+    /// Builder method to enable the occupancy heatmap, which shades each cell with
+    /// dynamic entities from green to red as it fills up to its inline capacity `N`.
+    /// Static entities are stored apart and aren't counted. Only drawn in debug mode.
+    pub fn heatmap(mut self, value: bool) -> Self {
+        self.heatmap = value;
+        self
+    }
+
+    /// Builder method to label each cell of the heatmap with its number of dynamic
+    /// entities. Requires the `debug_labels` feature.
+    pub fn heatmap_labels(mut self, value: bool) -> Self {
+        self.heatmap_labels = value;
+        self
+    }
+
     /// Builder method to set the shape of the grid in cell units.
     pub fn dimensions(mut self, value: impl Into<UVec2>) -> Self {
         self.dimensions = value.into();
@@ -44,33 +109,387 @@ impl<Marker: Component, const N: usize> UniformGrid2dPlugin<Marker, N> {
         self.anchor = value.into();
         self
     }
+
+    /// Builder method to set how the grid's cells are laid out in world space, e.g.
+    /// `GridProjection::Isometric` for diamond-shaped isometric tiles.
+    pub fn projection(mut self, value: GridProjection) -> Self {
+        self.projection = value;
+        self
+    }
+
+    /// Builder method to set the world axes the grid is laid out on, e.g.
+    /// `GridPlane::XZ` for 3D games with a top-down camera. Positions are
+    /// projected onto the plane and debug drawing happens on it. Defaults to
+    /// `GridPlane::XY`.
+    pub fn plane(mut self, value: GridPlane) -> Self {
+        self.plane = value;
+        self
+    }
+
+    /// Builder method to rotate, shear or scale the grid with an affine `transform`.
+    /// It is applied to the grid defined by `anchor`, `spacing` and `projection`.
+    pub fn transform(mut self, value: Affine2) -> Self {
+        self.transform = value;
+        self
+    }
+
+    /// Builder method to set a margin, as a fraction of `spacing`, that entities must
+    /// move past a cell border before they change cells. Prevents entities jittering
+    /// on a border from emitting an `Update` event every frame. Defaults to zero.
+    pub fn hysteresis(mut self, value: f32) -> Self {
+        self.hysteresis = value;
+        self
+    }
+
+    /// Builder method to set what happens to entities outside the grid. Defaults to
+    /// `OutOfBoundsPolicy::Remove`.
+    pub fn out_of_bounds_policy(mut self, value: OutOfBoundsPolicy) -> Self {
+        self.out_of_bounds_policy = value;
+        self
+    }
+
+    /// Builder method to split the entities in each cell into floors, either by
+    /// height bands or with the `GridFloor` component. Floor-aware queries like
+    /// `Grid::iter_neighbors_on_floors` then skip entities on other levels.
+    /// Defaults to `FloorMode::None`.
+    pub fn floor_mode(mut self, value: FloorMode) -> Self {
+        self.floor_mode = value;
+        self
+    }
+
+    /// Builder method to keep the entities in each cell sorted by `Entity` and to
+    /// write `GridEvent`s in `Entity` order, for lockstep simulations where every
+    /// peer must see the same results. Defaults to `false`.
+    pub fn deterministic(mut self, value: bool) -> Self {
+        self.deterministic = value;
+        self
+    }
+
+    /// Builder method to journal the changes to the grid's contents, for rollback
+    /// schedules that save the grid with `Grid::checkpoint` every tick and restore
+    /// it with `RewindGrid`. Grids with a `chunk_size` can't be rewound. Defaults
+    /// to `false`.
+    pub fn journal(mut self, value: bool) -> Self {
+        self.journal = value;
+        self
+    }
+
+    /// Builder method to stream the grid in chunks of `size` by `size` cells. Only
+    /// the chunks around a `ChunkAnchor` are loaded, and entities in the other
+    /// chunks are left out of the grid and its queries until their chunk loads.
+    /// Only the entities in chunks that load or unload are re-evaluated that frame.
+    pub fn chunks(mut self, size: u32) -> Self {
+        self.chunk_size = Some(size);
+        self
+    }
+
+    /// Builder method to enable the grid's `InfluenceMap`. Influence spreads from
+    /// each `InfluenceSource` according to `falloff`, and `decay` is the fraction
+    /// of influence removed each frame (`1.0` rebuilds the map every frame). A
+    /// source that stays put converges to `weight / decay`, see `InfluenceMap`.
+    pub fn influence(mut self, falloff: InfluenceFalloff, decay: f32) -> Self {
+        self.influence = Some((falloff, decay));
+        self
+    }
+
+    /// Builder method to enable the grid's `InterestMap`, which tracks the entities
+    /// each `GridObserver` can see and writes `InterestEnter` and `InterestExit`
+    /// events as they come into and go out of view.
+    pub fn interest(mut self, value: bool) -> Self {
+        self.interest = value;
+        self
+    }
+
+    /// Builder method to enable `GridCulling`, which hides the grid's entities
+    /// outside the cells the cameras can see.
+    #[cfg(feature = "render")]
+    pub fn culling(mut self, value: bool) -> Self {
+        self.culling = value;
+        self
+    }
+
+    /// Builder method to register `Diagnostic`s for the grid's health and the
+    /// time spent updating it. See `GridDiagnostics` for the available paths.
+    pub fn diagnostics(mut self, value: bool) -> Self {
+        self.diagnostics = value;
+        self
+    }
 }
 
-impl<Marker: Component, const N: usize> Default for UniformGrid2dPlugin<Marker, N> {
+impl<Marker: Component, const N: usize, P: GridPosition> Default
+    for UniformGrid2dPlugin<Marker, N, P>
+{
     fn default() -> Self {
         Self {
             dimensions: UVec2::ONE,
             spacing: Vec2::ONE,
             anchor: Vec2::ZERO,
+            projection: GridProjection::Orthogonal,
+            plane: GridPlane::XY,
+            transform: Affine2::IDENTITY,
+            hysteresis: 0.,
+            out_of_bounds_policy: OutOfBoundsPolicy::Remove,
+            floor_mode: FloorMode::None,
+            deterministic: false,
+            journal: false,
+            chunk_size: None,
             debug: false,
+            heatmap: false,
+            heatmap_labels: false,
+            influence: None,
+            interest: false,
+            #[cfg(feature = "render")]
+            culling: false,
+            diagnostics: false,
             marker: PhantomData,
         }
     }
 }
 
-impl<Marker: Component, const N: usize> Plugin for UniformGrid2dPlugin<Marker, N> {
+impl<Marker: Component, const N: usize, P: GridPosition> Plugin
+    for UniformGrid2dPlugin<Marker, N, P>
+{
     fn build(&self, app: &mut bevy::app::App) {
-        app.add_event::<GridEvent>()
+        let mut debug_settings = GridDebugSettings::<Marker, N>::default();
+        debug_settings.enabled = self.debug;
+        debug_settings.show_heatmap = self.heatmap;
+        debug_settings.show_labels = self.heatmap_labels;
+
+        app.register_type::<Grid<Marker, N>>()
+            .register_type::<GridCell<Marker, N>>()
+            .register_type::<GridDebugSettings<Marker, N>>()
+            .register_type::<TransformGridEvent<Marker, N>>()
+            .register_type::<GridEvent>()
+            .register_type::<GridOperation>()
+            .register_type::<GridFloor>()
+            .register_type::<GridStatic>()
+            .register_type::<GridLayers>()
+            .register_type::<InGrid>()
+            .register_type::<GridMembers>()
+            .register_type::<InfluenceSource>()
+            .add_event::<GridEvent>()
             .add_event::<TransformGridEvent<Marker, N>>()
             .insert_resource(
                 Grid::<Marker, N>::default()
                     .with_dimensions(self.dimensions)
                     .with_spacing(self.spacing)
-                    .with_anchor(self.anchor),
+                    .with_anchor(self.anchor)
+                    .with_projection(self.projection)
+                    .with_plane(self.plane)
+                    .with_transform(self.transform)
+                    .with_hysteresis(self.hysteresis)
+                    .with_out_of_bounds_policy(self.out_of_bounds_policy)
+                    .with_floor_mode(self.floor_mode)
+                    .with_deterministic(self.deterministic)
+                    .with_journal(self.journal)
+                    .with_chunk_size(self.chunk_size),
+            )
+            .insert_resource(debug_settings)
+            .init_resource::<GridHighlights<Marker, N>>()
+            .add_systems(
+                Update,
+                (
+                    update_grid_components::<Marker, N, P>,
+                    update_grid::<Marker, N, P>,
+                )
+                    .chain(),
+            )
+            .add_systems(PostUpdate, clear_grid_highlights::<Marker, N>);
+        if let Some((falloff, decay)) = self.influence {
+            app.insert_resource(
+                InfluenceMap::<Marker, N>::default()
+                    .with_falloff(falloff)
+                    .with_decay(decay),
+            )
+            .add_systems(
+                Update,
+                update_influence_map::<Marker, N>.after(update_grid::<Marker, N, P>),
+            );
+        }
+        if self.chunk_size.is_some() {
+            app.register_type::<ChunkAnchor>()
+                .register_type::<ChunkEvent<Marker, N>>()
+                .register_type::<ChunkOperation>()
+                .add_event::<ChunkEvent<Marker, N>>()
+                .add_systems(
+                    Update,
+                    update_grid_chunks::<Marker, N, P>
+                        .before(update_grid_components::<Marker, N, P>),
+                );
+        }
+        if self.interest {
+            app.register_type::<GridObserver>()
+                .register_type::<InterestEnter>()
+                .register_type::<InterestExit>()
+                .add_event::<InterestEnter>()
+                .add_event::<InterestExit>()
+                .init_resource::<InterestMap<Marker, N>>()
+                .add_systems(
+                    Update,
+                    update_interest_map::<Marker, N>.after(update_grid::<Marker, N, P>),
+                );
+        }
+        #[cfg(feature = "render")]
+        if self.culling {
+            app.init_resource::<GridCulling<Marker, N>>().add_systems(
+                Update,
+                update_grid_culling::<Marker, N>.after(update_grid::<Marker, N, P>),
+            );
+        }
+        if self.diagnostics {
+            let diagnostics = GridDiagnostics::<Marker, N>::default();
+            for path in diagnostics.paths() {
+                let diagnostic = Diagnostic::new(path.clone());
+                app.register_diagnostic(if path == diagnostics.update_time() {
+                    diagnostic.with_suffix("ms")
+                } else {
+                    diagnostic
+                });
+            }
+            app.insert_resource(diagnostics).add_systems(
+                Update,
+                update_grid_diagnostics::<Marker, N>.after(update_grid::<Marker, N, P>),
+            );
+        }
+    }
+
+    fn finish(&self, app: &mut bevy::app::App) {
+        #[cfg(feature = "render")]
+        if self.culling {
+            init_camera_views(app);
+        }
+        // Debug drawing needs the gizmo pipeline, so headless apps skip it entirely
+        if !app.is_plugin_added::<GizmoPlugin>() {
+            return;
+        }
+        #[cfg(feature = "render")]
+        init_camera_views(app);
+        app.init_gizmo_group::<GridGizmos<Marker>>()
+            .add_systems(
+                Update,
+                (
+                    sync_debug_gizmo_config::<Marker, N>,
+                    update_debug_grid_lines::<Marker, N>,
+                    update_debug_heatmap::<Marker, N>,
+                    update_debug_entity_links::<Marker, N, P>,
+                )
+                    .after(update_grid::<Marker, N, P>),
+            )
+            .add_systems(
+                PostUpdate,
+                update_debug_highlights::<Marker, N>.before(clear_grid_highlights::<Marker, N>),
+            );
+        #[cfg(feature = "debug_labels")]
+        app.add_systems(
+            Update,
+            update_debug_heatmap_labels::<Marker, N>.after(update_grid::<Marker, N, P>),
+        );
+    }
+}
+
+/// Track the views of the active cameras, once for all the grids that need them.
+#[cfg(feature = "render")]
+fn init_camera_views(app: &mut bevy::app::App) {
+    if !app.world().contains_resource::<CameraViews>() {
+        app.init_resource::<CameraViews>()
+            .add_systems(First, update_camera_views);
+    }
+}
+
+/// Indexes every entity with the `Marker` component in a `HexGrid<Marker, N>`,
+/// using the position supplied by its `P` component. See `GridPosition`.
+pub struct HexGrid2dPlugin<Marker: Component, const N: usize = 4, P: GridPosition = Transform> {
+    layout: HexLayout,
+    size: Vec2,
+    anchor: Vec2,
+    radius: u32,
+    plane: GridPlane,
+    debug: bool,
+    marker: PhantomData<(Marker, P)>,
+}
+
+impl<Marker: Component, const N: usize, P: GridPosition> HexGrid2dPlugin<Marker, N, P> {
+    /// Builder method to enable debug mode, which outlines every hex of the grid.
+    pub fn debug(mut self, value: bool) -> Self {
+        self.debug = value;
+        self
+    }
+
+    /// Builder method to set the orientation of the hexes. Defaults to
+    /// `HexLayout::PointyTop`.
+    pub fn layout(mut self, value: HexLayout) -> Self {
+        self.layout = value;
+        self
+    }
+
+    /// Builder method to set the distance from the center of a hex to its corners
+    /// in world-space units.
+    pub fn size(mut self, value: impl Into<Vec2>) -> Self {
+        self.size = value.into();
+        self
+    }
+
+    /// Builder method to set the point in world space of the center of hex `(0, 0)`.
+    /// Defaults to the origin.
+    pub fn anchor(mut self, value: impl Into<Vec2>) -> Self {
+        self.anchor = value.into();
+        self
+    }
+
+    /// Builder method to set the number of hexes from hex `(0, 0)` to the edge of
+    /// the grid.
+    pub fn radius(mut self, value: u32) -> Self {
+        self.radius = value;
+        self
+    }
+
+    /// Builder method to set the world axes the grid is laid out on. Defaults to
+    /// `GridPlane::XY`.
+    pub fn plane(mut self, value: GridPlane) -> Self {
+        self.plane = value;
+        self
+    }
+}
+
+impl<Marker: Component, const N: usize, P: GridPosition> Default for HexGrid2dPlugin<Marker, N, P> {
+    fn default() -> Self {
+        Self {
+            layout: HexLayout::PointyTop,
+            size: Vec2::ONE,
+            anchor: Vec2::ZERO,
+            radius: 0,
+            plane: GridPlane::XY,
+            debug: false,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<Marker: Component, const N: usize, P: GridPosition> Plugin for HexGrid2dPlugin<Marker, N, P> {
+    fn build(&self, app: &mut bevy::app::App) {
+        app.register_type::<HexGrid<Marker, N>>()
+            .register_type::<HexCell<Marker, N>>()
+            .register_type::<HexGridEvent>()
+            .register_type::<HexGridOperation>()
+            .add_event::<HexGridEvent>()
+            .insert_resource(
+                HexGrid::<Marker, N>::default()
+                    .with_layout(self.layout)
+                    .with_size(self.size)
+                    .with_anchor(self.anchor)
+                    .with_radius(self.radius)
+                    .with_plane(self.plane),
             )
-            .add_systems(Update, update_grid::<Marker, N>);
-        if self.debug {
-            app.add_systems(Update, update_debug_grid_lines::<Marker, N>);
+            .add_systems(Update, update_hex_grid::<Marker, N, P>);
+    }
+
+    fn finish(&self, app: &mut bevy::app::App) {
+        if !self.debug || !app.is_plugin_added::<GizmoPlugin>() {
+            return;
         }
+        app.init_gizmo_group::<HexGridGizmos<Marker>>().add_systems(
+            Update,
+            update_debug_hex_grid::<Marker, N>.after(update_hex_grid::<Marker, N, P>),
+        );
     }
 }
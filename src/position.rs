@@ -0,0 +1,130 @@
+use bevy::{
+    ecs::component::Component,
+    math::Vec3,
+    transform::components::{GlobalTransform, Transform},
+};
+
+/// A component that supplies the world-space position an entity is indexed at.
+/// The two axes of the grid's `plane` are used to find the entity's cell, and
+/// `FloorMode::Band` reads the axis along its normal to find the entity's floor.
+///
+/// Implemented for `Transform` (the default) and `GlobalTransform`. Implement it
+/// for your own components, e.g. a physics position or a logical tile position,
+/// and pick it with the plugin's `P` type parameter. Only entities whose
+/// position component changed are re-evaluated each frame.
+pub trait GridPosition: Component {
+    /// World-space position of the entity.
+    fn grid_position(&self) -> Vec3;
+}
+
+impl GridPosition for Transform {
+    #[inline]
+    fn grid_position(&self) -> Vec3 {
+        self.translation
+    }
+}
+
+impl GridPosition for GlobalTransform {
+    #[inline]
+    fn grid_position(&self) -> Vec3 {
+        self.translation()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        app::App,
+        ecs::hierarchy::ChildOf,
+        math::{IVec2, UVec2, Vec2},
+        transform::TransformPlugin,
+    };
+
+    use crate::{
+        component::GridCell, event::GridEvent, plugin::UniformGrid2dPlugin, test_util::drain,
+    };
+
+    use super::*;
+
+    #[derive(Component)]
+    struct TestMarker;
+
+    /// A logical tile position, one world unit per tile.
+    #[derive(Component)]
+    struct TilePosition(IVec2);
+
+    impl GridPosition for TilePosition {
+        fn grid_position(&self) -> Vec3 {
+            self.0.as_vec2().extend(0.)
+        }
+    }
+
+    #[test]
+    fn test_custom_position() {
+        let mut app = App::new();
+        app.add_plugins(
+            UniformGrid2dPlugin::<TestMarker, 4, TilePosition>::default()
+                .dimensions(UVec2::new(10, 10)),
+        );
+        // The `Transform` is far outside the grid, but only `TilePosition` counts
+        let entity = app
+            .world_mut()
+            .spawn((
+                TestMarker,
+                TilePosition(IVec2::new(2, 3)),
+                Transform::from_xyz(-100., -100., 0.),
+            ))
+            .id();
+        app.update();
+        let cell = app.world().get::<GridCell<TestMarker>>(entity).unwrap();
+        assert_eq!(cell.inner, UVec2::new(2, 3));
+        assert_eq!(drain::<GridEvent>(&mut app).len(), 1);
+
+        app.world_mut().get_mut::<TilePosition>(entity).unwrap().0 = IVec2::new(4, 3);
+        app.update();
+        let cell = app.world().get::<GridCell<TestMarker>>(entity).unwrap();
+        assert_eq!(cell.inner, UVec2::new(4, 3));
+        assert_eq!(drain::<GridEvent>(&mut app).len(), 1);
+
+        // Changing another position component doesn't re-evaluate the entity
+        app.world_mut()
+            .get_mut::<Transform>(entity)
+            .unwrap()
+            .translation = Vec3::new(500., 0., 0.);
+        app.update();
+        assert!(drain::<GridEvent>(&mut app).is_empty());
+    }
+
+    #[test]
+    fn test_global_transform_position() {
+        let mut app = App::new();
+        app.add_plugins((
+            TransformPlugin,
+            UniformGrid2dPlugin::<TestMarker, 4, GlobalTransform>::default()
+                .dimensions(UVec2::new(10, 10))
+                .spacing(Vec2::splat(10.)),
+        ));
+        let parent = app.world_mut().spawn(Transform::from_xyz(50., 0., 0.)).id();
+        let entity = app
+            .world_mut()
+            .spawn((TestMarker, Transform::from_xyz(5., 5., 0.), ChildOf(parent)))
+            .id();
+        // Transforms are propagated after the grid updates, so it takes a frame
+        app.update();
+        app.update();
+        let cell = app.world().get::<GridCell<TestMarker>>(entity).unwrap();
+        assert_eq!(cell.inner, UVec2::new(5, 0));
+
+        // Moving the parent moves the entity to another cell
+        app.world_mut()
+            .get_mut::<Transform>(parent)
+            .unwrap()
+            .translation = Vec3::new(70., 20., 0.);
+        drain::<GridEvent>(&mut app);
+        app.update();
+        app.update();
+        let cell = app.world().get::<GridCell<TestMarker>>(entity).unwrap();
+        assert_eq!(cell.inner, UVec2::new(7, 2));
+        assert_eq!(drain::<GridEvent>(&mut app).len(), 1);
+    }
+}
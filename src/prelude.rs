@@ -1,7 +1,25 @@
 pub use crate::{
-    component::GridCell,
+    command::{RestoreGrid, RewindGrid},
+    component::{
+        ChunkAnchor, GridCell, GridFloor, GridLayers, GridMembers, GridObserver, GridStatic,
+        HexCell, InGrid, InfluenceSource,
+    },
     error::GridError,
-    event::{GridEvent, GridOperation, TransformGridEvent},
-    plugin::UniformGrid2dPlugin,
-    resource::Grid,
+    event::{
+        ChunkEvent, ChunkOperation, GridEvent, GridOperation, HexGridEvent, HexGridOperation,
+        InterestEnter, InterestExit, TransformGridEvent,
+    },
+    gizmos::{GridGizmos, HexGridGizmos},
+    plugin::{HexGrid2dPlugin, UniformGrid2dPlugin},
+    position::GridPosition,
+    query::SpatialQuery,
+    resource::{
+        EntityKind, FloorMode, Grid, GridDebugSettings, GridDiagnostics, GridHighlights, GridPlane,
+        GridProjection, HexGrid, HexLayout, InfluenceFalloff, InfluenceMap, InterestMap,
+        OutOfBoundsPolicy, hex_distance, hex_ring, hex_spiral,
+    },
+    snapshot::{GridConfig, GridDelta, GridSnapshot},
 };
+
+#[cfg(feature = "render")]
+pub use crate::resource::GridCulling;
@@ -0,0 +1,212 @@
+use bevy::{
+    ecs::{
+        component::Component,
+        entity::EntityIndexSet,
+        query::{QueryData, QueryFilter, ROQueryItem},
+        system::{Query, Res, SystemParam},
+    },
+    math::{Rect, UVec2},
+};
+
+use crate::resource::Grid;
+
+/// System parameter joining the lookups of the `Grid<Marker, N>` resource with a
+/// `Query<D, F>`, yielding the query items of the entities found instead of the
+/// entities themselves. Entities that don't match the query are skipped.
+///
+/// The `_mut` lookups collect the entities into a set first, so the query can hand
+/// out mutable items for them without aliasing.
+#[derive(SystemParam)]
+pub struct SpatialQuery<
+    'w,
+    's,
+    Marker: Component,
+    D: QueryData + 'static,
+    F: QueryFilter + 'static = (),
+    const N: usize = 4,
+> {
+    grid: Res<'w, Grid<Marker, N>>,
+    query: Query<'w, 's, D, F>,
+}
+
+impl<'w, 's, Marker: Component, D: QueryData, F: QueryFilter, const N: usize>
+    SpatialQuery<'w, 's, Marker, D, F, N>
+{
+    /// Getter method for the `grid`.
+    #[inline]
+    pub fn grid(&self) -> &Grid<Marker, N> {
+        &self.grid
+    }
+
+    /// Getter method for the `query`.
+    #[inline]
+    pub fn query(&self) -> &Query<'w, 's, D, F> {
+        &self.query
+    }
+
+    /// Mutable getter method for the `query`.
+    #[inline]
+    pub fn query_mut(&mut self) -> &mut Query<'w, 's, D, F> {
+        &mut self.query
+    }
+
+    /// Query items of the entities in `cell`. See `Grid::get`.
+    #[inline]
+    pub fn get(&self, cell: UVec2) -> impl Iterator<Item = ROQueryItem<'_, D>> {
+        self.query.iter_many(self.grid.get(cell))
+    }
+
+    /// Query items of the entities in grid cells neighboring `cell`. See
+    /// `Grid::iter_neighbors`.
+    #[inline]
+    pub fn iter_neighbors(&self, cell: UVec2) -> impl Iterator<Item = ROQueryItem<'_, D>> {
+        self.query.iter_many(self.grid.iter_neighbors(cell))
+    }
+
+    /// Query items of the entities in grid cells within `radius` cells of `cell`.
+    /// See `Grid::iter_radius`.
+    #[inline]
+    pub fn iter_radius(
+        &self,
+        cell: UVec2,
+        radius: u32,
+    ) -> impl Iterator<Item = ROQueryItem<'_, D>> {
+        self.query.iter_many(self.grid.iter_radius(cell, radius))
+    }
+
+    /// Query items of the entities in grid cells overlapping the world-space
+    /// `rect`. See `Grid::iter_rect`.
+    #[inline]
+    pub fn iter_rect(&self, rect: Rect) -> impl Iterator<Item = ROQueryItem<'_, D>> {
+        self.query.iter_many(self.grid.iter_rect(rect))
+    }
+
+    /// Mutable query items of the entities in `cell`. See `get`.
+    #[inline]
+    pub fn get_mut(&mut self, cell: UVec2) -> impl Iterator<Item = D::Item<'_>> {
+        let entities: EntityIndexSet = self.grid.get(cell).collect();
+        self.iter_many_mut(entities)
+    }
+
+    /// Mutable query items of the entities in grid cells neighboring `cell`. See
+    /// `iter_neighbors`.
+    #[inline]
+    pub fn iter_neighbors_mut(&mut self, cell: UVec2) -> impl Iterator<Item = D::Item<'_>> {
+        let entities: EntityIndexSet = self.grid.iter_neighbors(cell).collect();
+        self.iter_many_mut(entities)
+    }
+
+    /// Mutable query items of the entities in grid cells within `radius` cells of
+    /// `cell`. See `iter_radius`.
+    #[inline]
+    pub fn iter_radius_mut(
+        &mut self,
+        cell: UVec2,
+        radius: u32,
+    ) -> impl Iterator<Item = D::Item<'_>> {
+        let entities: EntityIndexSet = self.grid.iter_radius(cell, radius).collect();
+        self.iter_many_mut(entities)
+    }
+
+    /// Mutable query items of the entities in grid cells overlapping the
+    /// world-space `rect`. See `iter_rect`.
+    #[inline]
+    pub fn iter_rect_mut(&mut self, rect: Rect) -> impl Iterator<Item = D::Item<'_>> {
+        let entities: EntityIndexSet = self.grid.iter_rect(rect).collect();
+        self.iter_many_mut(entities)
+    }
+
+    /// Mutable query items of a set of `entities`, in the order they were found.
+    #[inline]
+    fn iter_many_mut(&mut self, entities: EntityIndexSet) -> impl Iterator<Item = D::Item<'_>> {
+        self.query.iter_many_unique_mut(entities)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        ecs::{entity::Entity, query::Without, system::RunSystemOnce, world::World},
+        math::Vec2,
+    };
+
+    use super::*;
+
+    #[derive(Component)]
+    struct TestMarker;
+
+    #[derive(Component, Debug, Default, PartialEq)]
+    struct Health(u32);
+
+    #[derive(Component)]
+    struct Dead;
+
+    type Health2d<'w, 's> = SpatialQuery<'w, 's, TestMarker, &'static mut Health, Without<Dead>>;
+
+    /// A world with two living entities side by side, a dead one next to them,
+    /// and one entity in two cells at once.
+    fn world() -> (World, [Entity; 4]) {
+        let mut world = World::new();
+        let entities = [(); 4].map(|_| world.spawn(Health::default()).id());
+        let [first, second, dead, twice] = entities;
+        world.entity_mut(dead).insert(Dead);
+        let mut grid = Grid::<TestMarker>::default()
+            .with_dimensions(UVec2::new(10, 10))
+            .with_spacing(Vec2::splat(10.));
+        grid.insert(first, UVec2::new(1, 1)).unwrap();
+        grid.insert(second, UVec2::new(2, 1)).unwrap();
+        grid.insert(dead, UVec2::new(2, 2)).unwrap();
+        grid.insert(twice, UVec2::new(5, 5)).unwrap();
+        grid.insert(twice, UVec2::new(5, 6)).unwrap();
+        world.insert_resource(grid);
+        (world, entities)
+    }
+
+    fn health(world: &World, entities: [Entity; 4]) -> [u32; 4] {
+        entities.map(|entity| world.get::<Health>(entity).unwrap().0)
+    }
+
+    #[test]
+    fn test_lookups() {
+        let (mut world, _) = world();
+        let counts = world
+            .run_system_once(|query: Health2d| {
+                let rect = Rect::new(0., 0., 29., 29.);
+                [
+                    query.get(UVec2::new(1, 1)).count(),
+                    query.get(UVec2::new(2, 2)).count(),
+                    query.iter_neighbors(UVec2::new(1, 1)).count(),
+                    query.iter_radius(UVec2::new(5, 5), 1).count(),
+                    query.iter_rect(rect).count(),
+                ]
+            })
+            .unwrap();
+
+        // The dead entity is skipped, and the one in two cells is found twice
+        assert_eq!(counts, [1, 0, 1, 2, 2]);
+    }
+
+    #[test]
+    fn test_lookups_mut() {
+        let (mut world, entities) = world();
+        world
+            .run_system_once(|mut query: Health2d| {
+                for mut health in query.get_mut(UVec2::new(1, 1)) {
+                    health.0 += 1;
+                }
+                for mut health in query.iter_neighbors_mut(UVec2::new(1, 1)) {
+                    health.0 += 10;
+                }
+                for mut health in query.iter_radius_mut(UVec2::new(5, 5), 1) {
+                    health.0 += 100;
+                }
+                for mut health in query.iter_rect_mut(Rect::new(0., 0., 29., 29.)) {
+                    health.0 += 1000;
+                }
+            })
+            .unwrap();
+
+        // The dead entity is skipped, and the one in two cells is changed once
+        assert_eq!(health(&world, entities), [1001, 1010, 0, 100]);
+    }
+}
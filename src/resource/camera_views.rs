@@ -0,0 +1,130 @@
+use bevy::{
+    ecs::resource::Resource,
+    math::{Ray3d, Rect, Vec2},
+};
+
+use crate::resource::GridPlane;
+
+/// What a single active camera sees, as the rays through its viewport corners.
+#[derive(Clone, Copy, Debug)]
+pub struct CameraView {
+    /// Rays through the corners of the camera's viewport.
+    pub corners: [Ray3d; 4],
+    /// Size of the camera's viewport in logical pixels.
+    pub viewport_size: Vec2,
+}
+
+impl CameraView {
+    /// The rectangle the camera sees on `plane` through the origin, in plane
+    /// coordinates, and the number of world units covered by one logical pixel.
+    /// `None` if the camera can see past the horizon of the plane.
+    pub fn visible_rect(&self, plane: GridPlane) -> Option<(Rect, f32)> {
+        let normal = plane.normal();
+        let mut rect = Rect::EMPTY;
+        for ray in self.corners {
+            let direction = ray.direction.dot(normal);
+            if direction.abs() <= f32::EPSILON {
+                return None;
+            }
+            let distance = -ray.origin.dot(normal) / direction;
+            if distance < 0. {
+                return None;
+            }
+            rect = rect.union_point(plane.project(ray.get_point(distance)));
+        }
+        let world_per_pixel = rect.width() / self.viewport_size.x.max(1.);
+        Some((rect, world_per_pixel))
+    }
+}
+
+/// Views of all active cameras, refreshed every frame. Used to skip work for
+/// the parts of a grid that aren't on screen.
+#[derive(Resource, Default, Clone, Debug)]
+pub struct CameraViews {
+    pub views: Vec<CameraView>,
+}
+
+impl CameraViews {
+    /// The union of the rectangles seen by every active camera on `plane`, and
+    /// the smallest number of world units covered by a pixel. `None` if there
+    /// are no cameras or one of them sees an unbounded area.
+    pub fn visible_rect(&self, plane: GridPlane) -> Option<(Rect, f32)> {
+        let mut views = self.views.iter();
+        let mut visible = views.next()?.visible_rect(plane)?;
+        for view in views {
+            let (rect, world_per_pixel) = view.visible_rect(plane)?;
+            visible.0 = visible.0.union(rect);
+            visible.1 = visible.1.min(world_per_pixel);
+        }
+        Some(visible)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::math::{Dir3, Vec3};
+
+    fn view(min: Vec2, max: Vec2, direction: Dir3) -> CameraView {
+        let ray = |x: f32, y: f32| Ray3d::new(Vec3::new(x, y, 10.), direction);
+        CameraView {
+            corners: [
+                ray(min.x, min.y),
+                ray(max.x, min.y),
+                ray(min.x, max.y),
+                ray(max.x, max.y),
+            ],
+            viewport_size: Vec2::new(800., 600.),
+        }
+    }
+
+    #[test]
+    fn test_visible_rect() {
+        let view = view(Vec2::new(0., 0.), Vec2::new(400., 300.), Dir3::NEG_Z);
+        let (rect, world_per_pixel) = view.visible_rect(GridPlane::XY).unwrap();
+        assert_eq!(rect, Rect::new(0., 0., 400., 300.));
+        assert_eq!(world_per_pixel, 0.5);
+        // Looking straight down the z axis shows nothing of the XZ plane
+        assert!(view.visible_rect(GridPlane::XZ).is_none());
+
+        // A camera looking away from the plane sees nothing of it
+        let view = CameraView {
+            corners: view.corners.map(|ray| Ray3d::new(ray.origin, Dir3::Z)),
+            ..view
+        };
+        assert!(view.visible_rect(GridPlane::XY).is_none());
+    }
+
+    #[test]
+    fn test_visible_rect_union() {
+        let camera_views = CameraViews {
+            views: vec![
+                view(Vec2::new(0., 0.), Vec2::new(400., 300.), Dir3::NEG_Z),
+                view(Vec2::new(-100., 50.), Vec2::new(700., 650.), Dir3::NEG_Z),
+            ],
+        };
+        let (rect, world_per_pixel) = camera_views.visible_rect(GridPlane::XY).unwrap();
+        assert_eq!(rect, Rect::new(-100., 0., 700., 650.));
+        assert_eq!(world_per_pixel, 0.5);
+
+        assert!(CameraViews::default().visible_rect(GridPlane::XY).is_none());
+    }
+
+    #[test]
+    fn test_visible_rect_xz() {
+        // A top-down 3D camera 10 units above the ground
+        let ray = |x: f32, z: f32| Ray3d::new(Vec3::new(x, 10., z), Dir3::NEG_Y);
+        let view = CameraView {
+            corners: [
+                ray(-20., -10.),
+                ray(20., -10.),
+                ray(-20., 10.),
+                ray(20., 10.),
+            ],
+            viewport_size: Vec2::new(800., 400.),
+        };
+        let (rect, world_per_pixel) = view.visible_rect(GridPlane::XZ).unwrap();
+        assert_eq!(rect, Rect::new(-20., -10., 20., 10.));
+        assert_eq!(world_per_pixel, 0.05);
+    }
+}
@@ -1,15 +1,186 @@
-use std::marker::PhantomData;
+use std::{marker::PhantomData, ops::RangeInclusive};
 
 use bevy::{
-    ecs::{component::Component, entity::Entity, resource::Resource},
-    math::{IVec2, UVec2, Vec2, Vec3, Vec3Swizzles},
+    ecs::{
+        component::Component,
+        entity::{Entity, EntityHashMap, EntityHashSet},
+        resource::Resource,
+    },
+    math::{Affine2, Affine3A, IVec2, Mat2, Rect, UVec2, Vec2, Vec3, Vec3Swizzles},
+    prelude::{ReflectComponent, ReflectResource},
+    reflect::Reflect,
 };
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 use smallvec::SmallVec;
 
-use crate::error::GridError;
+use crate::{
+    component::GridLayers,
+    error::GridError,
+    snapshot::{GridConfig, GridDelta, GridSnapshot},
+    type_path::impl_marker_type_path,
+};
+
+/// How the cells of a grid are laid out in world space.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GridProjection {
+    /// Cells are rectangles of `spacing`.
+    #[default]
+    Orthogonal,
+    /// Cells are diamonds `spacing.x` wide and `spacing.y` tall, e.g. `(64, 32)`
+    /// for 2:1 isometric tiles. The cell `x` axis runs right and up, the `y` axis
+    /// left and up, with the bottom corner of cell `(0, 0)` at `anchor`.
+    Isometric,
+}
+
+/// The two world axes a grid is laid out on. Positions are projected onto the
+/// plane by dropping the third axis, so entities at any depth along the plane's
+/// normal are indexed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GridPlane {
+    /// Grid `x` and `y` run along world `x` and `y`, for 2D games.
+    #[default]
+    XY,
+    /// Grid `x` and `y` run along world `x` and `z`, for 3D games with a
+    /// top-down camera.
+    XZ,
+    /// Grid `x` and `y` run along world `y` and `z`.
+    YZ,
+}
+
+impl GridPlane {
+    /// Project a world-space `position` onto the plane.
+    #[inline]
+    pub fn project(self, position: Vec3) -> Vec2 {
+        match self {
+            GridPlane::XY => position.xy(),
+            GridPlane::XZ => position.xz(),
+            GridPlane::YZ => position.yz(),
+        }
+    }
+
+    /// World-space position of the point at `position` on the plane, offset
+    /// by `depth` along the plane's normal.
+    #[inline]
+    pub fn unproject(self, position: Vec2, depth: f32) -> Vec3 {
+        match self {
+            GridPlane::XY => Vec3::new(position.x, position.y, depth),
+            GridPlane::XZ => Vec3::new(position.x, depth, position.y),
+            GridPlane::YZ => Vec3::new(depth, position.x, position.y),
+        }
+    }
+
+    /// The world axis perpendicular to the plane.
+    #[inline]
+    pub fn normal(self) -> Vec3 {
+        match self {
+            GridPlane::XY => Vec3::Z,
+            GridPlane::XZ => Vec3::Y,
+            GridPlane::YZ => Vec3::X,
+        }
+    }
+
+    /// Project a 3D `affine` transform onto the plane, dropping any rotation
+    /// out of the plane.
+    #[inline]
+    pub fn project_affine(self, affine: Affine3A) -> Affine2 {
+        let axis = |axis: Vec2| self.project(affine.transform_vector3(self.unproject(axis, 0.)));
+        Affine2::from_mat2_translation(
+            Mat2::from_cols(axis(Vec2::X), axis(Vec2::Y)),
+            self.project(affine.translation.into()),
+        )
+    }
+}
+
+/// How the entities in a grid cell are split into floors, so that e.g. units on
+/// a bridge aren't neighbors of units on the road below.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FloorMode {
+    /// Every entity is on floor `0`.
+    #[default]
+    None,
+    /// Floors are bands of `height` world units along the normal of the grid's
+    /// `plane`, with floor `0` starting at the plane through the origin.
+    Band { height: f32 },
+    /// Floors are set with the `GridFloor` component. Entities without one are
+    /// on floor `0`.
+    Explicit,
+}
+
+/// Which entities of a grid a query visits. See `GridStatic`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EntityKind {
+    /// Both static and dynamic entities.
+    #[default]
+    All,
+    /// Only entities marked `GridStatic`.
+    Static,
+    /// Only entities without `GridStatic`.
+    Dynamic,
+}
+
+/// An entity in a grid cell, the floor it is on and its layers.
+#[derive(Clone, Copy, Debug)]
+struct GridEntry {
+    entity: Entity,
+    floor: i32,
+    layers: GridLayers,
+}
+
+/// Contents of the cells and overflow list that changed since the last checkpoint,
+/// as they were before their first change. See `Grid::checkpoint`.
+#[derive(Default)]
+struct GridJournal<const N: usize> {
+    cells: FxHashMap<UVec2, SmallVec<[GridEntry; N]>>,
+    /// Whether each entity was in the overflow list.
+    overflow: FxHashMap<Entity, bool>,
+}
+
+impl<const N: usize> GridJournal<N> {
+    /// Record `entries` as the contents of `cell`, unless it already changed
+    /// since the last checkpoint.
+    #[inline]
+    fn record(&mut self, cell: UVec2, entries: &[GridEntry]) {
+        self.cells
+            .entry(cell)
+            .or_insert_with(|| SmallVec::from_slice(entries));
+    }
+
+    /// Record whether `entity` was in the overflow list, unless it already
+    /// changed since the last checkpoint.
+    #[inline]
+    fn record_overflow(&mut self, entity: Entity, overflow: bool) {
+        self.overflow.entry(entity).or_insert(overflow);
+    }
+}
+
+/// What happens to an entity whose position is outside the grid.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OutOfBoundsPolicy {
+    /// Remove the entity from the grid along with its `GridCell`.
+    #[default]
+    Remove,
+    /// Keep the entity in the cell on the edge of the grid closest to its position.
+    ClampToEdge,
+    /// Keep the entity in the last cell it was in. Entities that start outside
+    /// the grid are not inserted.
+    KeepLastCell,
+    /// Remove the entity from its cell and its `GridCell`, but keep track of it in
+    /// the grid's overflow list. See `Grid::iter_overflow`.
+    Overflow,
+}
 
-#[derive(Resource)]
+/// Spatial index of every entity with the `Marker` component. Usually a resource,
+/// but can also be a component so several grids can share one `Marker`; see `InGrid`.
+///
+/// The configuration can be inspected and edited through reflection. The contents
+/// of the grid are not reflected; see `Grid::snapshot` for saving them.
+#[derive(Resource, Component, Reflect)]
+#[reflect(Resource, Component, type_path = false)]
 pub struct Grid<Marker: Component, const N: usize = 4> {
     /// Shape of the grid in cell units.
     dimensions: UVec2,
@@ -17,19 +188,113 @@ pub struct Grid<Marker: Component, const N: usize = 4> {
     spacing: Vec2,
     /// Point in world space to anchor the grid. Defaults to the origin.
     anchor: Vec2,
-    data: FxHashMap<UVec2, SmallVec<[Entity; N]>>,
+    /// How cells are laid out in world space. Defaults to `GridProjection::Orthogonal`.
+    projection: GridProjection,
+    /// World axes the grid is laid out on. Defaults to `GridPlane::XY`.
+    plane: GridPlane,
+    /// Rotation, shear and scale applied to the grid defined by `anchor`,
+    /// `spacing` and `projection`. Defaults to the identity.
+    transform: Affine2,
+    /// Transform from the grid's parent space to world space on the grid's `plane`.
+    /// Follows the `GlobalTransform` of the entity a grid component is on.
+    #[reflect(ignore)]
+    frame: Affine2,
+    /// Conversion from continuous cell coordinates to world space, cached
+    /// from the fields above.
+    #[reflect(ignore)]
+    cell_to_world: Affine2,
+    /// Inverse of `frame`, cached for converting world-space positions.
+    #[reflect(ignore)]
+    world_to_local: Affine2,
+    /// Inverse of `transform`, cached for converting positions relative to the
+    /// grid's entity. The `spacing` and `anchor` are undone by division instead,
+    /// so positions on a cell border land in the cell past it.
+    #[reflect(ignore)]
+    local_to_layout: Affine2,
+    /// Configuration the cached conversions were computed from. Differs from
+    /// the current configuration after it was edited through reflection.
+    #[reflect(ignore)]
+    synced: Option<GridConfig>,
+    /// Margin, as a fraction of `spacing`, that an entity must move past a cell
+    /// border before it changes cells. Defaults to zero.
+    hysteresis: f32,
+    /// What happens to entities outside the grid. Defaults to `OutOfBoundsPolicy::Remove`.
+    out_of_bounds_policy: OutOfBoundsPolicy,
+    /// How entities are split into floors. Defaults to `FloorMode::None`.
+    floor_mode: FloorMode,
+    /// Whether the entities in each cell are kept sorted by `Entity`, and events
+    /// are written in `Entity` order, so results match across lockstep peers.
+    /// Defaults to `false`.
+    deterministic: bool,
+    /// Size of the chunks the grid is streamed in, in cells along each axis.
+    /// Entities in chunks that aren't loaded are left out of the grid. Defaults
+    /// to `None`, which keeps every cell loaded.
+    chunk_size: Option<u32>,
+    /// Chunks around a `ChunkAnchor`, when `chunk_size` is set.
+    #[reflect(ignore)]
+    loaded_chunks: FxHashSet<UVec2>,
+    /// Chunks that unloaded since the grid was last updated.
+    #[reflect(ignore)]
+    deactivated_chunks: Vec<UVec2>,
+    /// Chunks that loaded since the grid was last updated.
+    #[reflect(ignore)]
+    activated_chunks: Vec<UVec2>,
+    /// Entities left out of the grid until their chunk loads, by chunk.
+    #[reflect(ignore)]
+    suspended: FxHashMap<UVec2, EntityHashSet>,
+    /// Chunk each of the `suspended` entities is in.
+    #[reflect(ignore)]
+    suspended_chunks: EntityHashMap<UVec2>,
+    #[reflect(ignore)]
+    data: FxHashMap<UVec2, SmallVec<[GridEntry; N]>>,
+    /// Entities outside the grid when using `OutOfBoundsPolicy::Overflow`.
+    #[reflect(ignore)]
+    overflow: FxHashSet<Entity>,
+    /// Entities marked `GridStatic`, sorted by row, column and `Entity` so the
+    /// entities of each cell are next to each other.
+    #[reflect(ignore)]
+    statics: Vec<(UVec2, GridEntry)>,
+    /// Changes since the last checkpoint, when journaling is enabled.
+    #[reflect(ignore)]
+    journal: Option<GridJournal<N>>,
+    #[reflect(ignore)]
     marker: PhantomData<Marker>,
 }
 
+impl_marker_type_path!(Grid<Marker, N>);
+
 impl<Marker: Component, const N: usize> Default for Grid<Marker, N> {
     fn default() -> Self {
-        Self {
+        let mut grid = Self {
             dimensions: UVec2::ONE,
             spacing: Vec2::ONE,
             anchor: Vec2::ZERO,
+            projection: GridProjection::default(),
+            plane: GridPlane::default(),
+            transform: Affine2::IDENTITY,
+            frame: Affine2::IDENTITY,
+            cell_to_world: Affine2::IDENTITY,
+            world_to_local: Affine2::IDENTITY,
+            local_to_layout: Affine2::IDENTITY,
+            synced: None,
+            hysteresis: 0.,
+            out_of_bounds_policy: OutOfBoundsPolicy::default(),
+            floor_mode: FloorMode::default(),
+            deterministic: false,
+            chunk_size: None,
+            loaded_chunks: FxHashSet::default(),
+            deactivated_chunks: Vec::new(),
+            activated_chunks: Vec::new(),
+            suspended: FxHashMap::default(),
+            suspended_chunks: EntityHashMap::default(),
             data: FxHashMap::default(),
+            overflow: FxHashSet::default(),
+            statics: Vec::new(),
+            journal: None,
             marker: PhantomData,
-        }
+        };
+        grid.sync_affine();
+        grid
     }
 }
 
@@ -52,29 +317,412 @@ impl<Marker: Component, const N: usize> Grid<Marker, N> {
         self.anchor
     }
 
+    /// Getter method for the grid's `projection`.
+    #[inline]
+    pub fn projection(&self) -> GridProjection {
+        self.projection
+    }
+
+    /// Getter method for the grid's `plane`.
+    #[inline]
+    pub fn plane(&self) -> GridPlane {
+        self.plane
+    }
+
+    /// Getter method for the grid's `transform`.
+    #[inline]
+    pub fn transform(&self) -> Affine2 {
+        self.transform
+    }
+
+    /// Getter method for the grid's `frame`.
+    #[inline]
+    pub fn frame(&self) -> Affine2 {
+        self.frame
+    }
+
+    /// Getter method for the grid's `hysteresis`.
+    #[inline]
+    pub fn hysteresis(&self) -> f32 {
+        self.hysteresis
+    }
+
+    /// Getter method for the grid's `out_of_bounds_policy`.
+    #[inline]
+    pub fn out_of_bounds_policy(&self) -> OutOfBoundsPolicy {
+        self.out_of_bounds_policy
+    }
+
+    /// Getter method for the grid's `floor_mode`.
+    #[inline]
+    pub fn floor_mode(&self) -> FloorMode {
+        self.floor_mode
+    }
+
     /// Builder method to set the grid's `dimensions`.
     pub fn with_dimensions(mut self, value: impl Into<UVec2>) -> Self {
         self.dimensions = value.into();
+        self.sync_affine();
         self
     }
 
     /// Builder method to set the grid's `spacing`.
     pub fn with_spacing(mut self, value: impl Into<Vec2>) -> Self {
-        self.spacing = value.into();
+        self.set_spacing(value);
         self
     }
 
     /// Builder method to set the grid's `anchor`.
     pub fn with_anchor(mut self, value: impl Into<Vec2>) -> Self {
-        self.anchor = value.into();
+        self.set_anchor(value);
+        self
+    }
+
+    /// Builder method to set the grid's `projection`.
+    pub fn with_projection(mut self, value: GridProjection) -> Self {
+        self.projection = value;
+        self.sync_affine();
+        self
+    }
+
+    /// Builder method to set the grid's `plane`.
+    pub fn with_plane(mut self, value: GridPlane) -> Self {
+        self.plane = value;
+        self.sync_affine();
+        self
+    }
+
+    /// Builder method to set the grid's `transform`, e.g. to rotate or shear it.
+    pub fn with_transform(mut self, value: Affine2) -> Self {
+        self.set_transform(value);
+        self
+    }
+
+    /// Builder method to set the grid's `hysteresis`. Negative values are clamped to zero.
+    pub fn with_hysteresis(mut self, value: f32) -> Self {
+        self.hysteresis = value.max(0.);
+        self.sync_affine();
+        self
+    }
+
+    /// Builder method to set the grid's `out_of_bounds_policy`.
+    pub fn with_out_of_bounds_policy(mut self, value: OutOfBoundsPolicy) -> Self {
+        self.out_of_bounds_policy = value;
+        self.sync_affine();
+        self
+    }
+
+    /// Getter method for the grid's `deterministic` flag.
+    #[inline]
+    pub fn deterministic(&self) -> bool {
+        self.deterministic
+    }
+
+    /// Builder method to set the grid's `deterministic` flag.
+    pub fn with_deterministic(mut self, value: bool) -> Self {
+        self.deterministic = value;
+        self.sync_affine();
+        self
+    }
+
+    /// Getter method for the grid's `chunk_size`.
+    #[inline]
+    pub fn chunk_size(&self) -> Option<u32> {
+        self.chunk_size
+    }
+
+    /// Builder method to set the grid's `chunk_size`.
+    pub fn with_chunk_size(mut self, value: Option<u32>) -> Self {
+        self.chunk_size = value.map(|size| size.max(1));
+        self.sync_affine();
+        self
+    }
+
+    /// Builder method to set the grid's `floor_mode`.
+    pub fn with_floor_mode(mut self, value: FloorMode) -> Self {
+        self.floor_mode = value;
+        self.sync_affine();
+        self
+    }
+
+    /// Builder method to set every field of the grid's configuration at once.
+    pub fn with_config(mut self, config: GridConfig) -> Self {
+        self.apply_config(config);
+        self
+    }
+
+    /// The grid's configuration, without its contents.
+    pub fn config(&self) -> GridConfig {
+        GridConfig {
+            dimensions: self.dimensions,
+            spacing: self.spacing,
+            anchor: self.anchor,
+            projection: self.projection,
+            plane: self.plane,
+            transform: self.transform,
+            hysteresis: self.hysteresis,
+            out_of_bounds_policy: self.out_of_bounds_policy,
+            floor_mode: self.floor_mode,
+            deterministic: self.deterministic,
+            chunk_size: self.chunk_size,
+        }
+    }
+
+    fn apply_config(&mut self, config: GridConfig) {
+        self.dimensions = config.dimensions;
+        self.spacing = config.spacing;
+        self.anchor = config.anchor;
+        self.projection = config.projection;
+        self.plane = config.plane;
+        self.transform = config.transform;
+        self.hysteresis = config.hysteresis.max(0.);
+        self.out_of_bounds_policy = config.out_of_bounds_policy;
+        self.floor_mode = config.floor_mode;
+        self.deterministic = config.deterministic;
+        self.chunk_size = config.chunk_size.map(|size| size.max(1));
+        self.sync_affine();
+    }
+
+    /// Capture the grid's configuration and contents. See `GridSnapshot`.
+    pub fn snapshot(&self) -> GridSnapshot {
+        let mut cells: Vec<_> = self.data.keys().copied().collect();
+        cells.sort_unstable_by_key(|cell| (cell.y, cell.x));
+        let cells = cells
+            .into_iter()
+            .map(|cell| {
+                let entities = self
+                    .get_of_kind_with_floors(cell, EntityKind::Dynamic)
+                    .collect();
+                (cell, entities)
+            })
+            .collect();
+        let mut layers: Vec<_> = self
+            .data
+            .values()
+            .flatten()
+            .chain(self.statics.iter().map(|(_, entry)| entry))
+            .filter(|entry| entry.layers != GridLayers::DEFAULT)
+            .map(|entry| (entry.entity, entry.layers))
+            .collect();
+        layers.sort_unstable_by_key(|&(entity, _)| entity);
+        let statics = self
+            .statics
+            .chunk_by(|(a, _), (b, _)| a == b)
+            .map(|run| {
+                let entities = run
+                    .iter()
+                    .map(|(_, entry)| (entry.entity, entry.floor))
+                    .collect();
+                (run[0].0, entities)
+            })
+            .collect();
+        GridSnapshot {
+            config: self.config(),
+            cells,
+            overflow: self.sorted_overflow(),
+            statics,
+            layers,
+        }
+    }
+
+    /// Replace the grid's configuration and contents with a `snapshot`. Entities in
+    /// cells outside the restored `dimensions` are dropped. Snapshots don't record
+    /// which chunks were loaded, so entities in chunks that aren't loaded now are
+    /// suspended until they load, and suspended entities the snapshot doesn't
+    /// place stay suspended. The `GridCell` components of the entities are left as
+    /// they are; see `RestoreGrid` for a command that updates them too.
+    pub fn restore(&mut self, snapshot: &GridSnapshot) {
+        self.apply_config(snapshot.config);
+        let mut suspended = std::mem::take(&mut self.suspended_chunks);
+        self.reset();
+        let layers: EntityHashMap<_> = snapshot.layers.iter().copied().collect();
+        let layers_of = |entity| layers.get(&entity).copied().unwrap_or_default();
+        for (cell, entities) in &snapshot.cells {
+            if !self.contains_cell(*cell) {
+                continue;
+            }
+            if !self.is_cell_loaded(*cell) {
+                let chunk = self.cell_to_chunk(*cell);
+                suspended.extend(entities.iter().map(|&(entity, _)| (entity, chunk)));
+                continue;
+            }
+            for (entity, _) in entities {
+                suspended.remove(entity);
+            }
+            let mut entries: SmallVec<[GridEntry; N]> = entities
+                .iter()
+                .map(|&(entity, floor)| GridEntry {
+                    entity,
+                    floor,
+                    layers: layers_of(entity),
+                })
+                .collect();
+            if self.deterministic {
+                entries.sort_unstable_by_key(|entry| entry.entity);
+            }
+            if let Some(journal) = &mut self.journal {
+                journal.record(*cell, &[]);
+            }
+            self.data.insert(*cell, entries);
+        }
+        for &entity in &snapshot.overflow {
+            suspended.remove(&entity);
+            self.insert_overflow(entity);
+        }
+        let mut statics = Vec::new();
+        for (cell, entities) in &snapshot.statics {
+            for &(entity, floor) in entities {
+                if !self.contains_cell(*cell) {
+                    continue;
+                }
+                match self.is_cell_loaded(*cell) {
+                    true => {
+                        suspended.remove(&entity);
+                        statics.push((entity, *cell, floor, layers_of(entity)));
+                    }
+                    false => {
+                        suspended.insert(entity, self.cell_to_chunk(*cell));
+                    }
+                }
+            }
+        }
+        self.extend_static(statics);
+        for (entity, chunk) in suspended {
+            if !self.is_chunk_loaded(chunk) {
+                self.suspend(entity, chunk);
+            }
+        }
+    }
+
+    /// Builder method to journal the changes to the grid's contents, so they can
+    /// be undone with `checkpoint` and `rewind`. Static entities and the chunks
+    /// entities are suspended in are not journaled, so grids with a `chunk_size`
+    /// can't be rewound.
+    pub fn with_journal(mut self, value: bool) -> Self {
+        self.journal = value.then(GridJournal::default);
         self
     }
 
+    /// Getter method for whether the grid journals its changes. See `with_journal`.
+    #[inline]
+    pub fn journal(&self) -> bool {
+        self.journal.is_some()
+    }
+
+    /// Take the changes journaled since the previous checkpoint, as a `GridDelta`
+    /// that `rewind` can undo them with. Costs O(changed cells), so it can be done
+    /// every tick of a rollback schedule. Empty when journaling is disabled.
+    pub fn checkpoint(&mut self) -> GridDelta {
+        let Some(journal) = self.journal.as_mut().map(std::mem::take) else {
+            return GridDelta::default();
+        };
+        let mut layers: Vec<_> = journal
+            .cells
+            .values()
+            .flatten()
+            .filter(|entry| entry.layers != GridLayers::DEFAULT)
+            .map(|entry| (entry.entity, entry.layers))
+            .collect();
+        layers.sort_unstable_by_key(|&(entity, _)| entity);
+        let mut cells: Vec<_> = journal
+            .cells
+            .into_iter()
+            .map(|(cell, entries)| {
+                let entities = entries
+                    .iter()
+                    .map(|entry| (entry.entity, entry.floor))
+                    .collect();
+                (cell, entities)
+            })
+            .collect();
+        cells.sort_unstable_by_key(|&(cell, _)| (cell.y, cell.x));
+        let mut overflow: Vec<_> = journal.overflow.into_iter().collect();
+        overflow.sort_unstable();
+        GridDelta {
+            cells,
+            overflow,
+            layers,
+        }
+    }
+
+    /// Undo the changes since the last checkpoint, and then the ones in `delta`.
+    /// To go back several checkpoints, rewind their deltas from newest to oldest.
+    ///
+    /// Returns the entities whose cell, floor or layers may have changed, with the
+    /// cell, floor and layers they have now, or `None` if they are no longer in a
+    /// cell. The `GridCell` components are left as they are; see `RewindGrid` for
+    /// a command that updates them too.
+    ///
+    /// Grids with a `chunk_size` are left as they are, since rewinding could put
+    /// entities back into chunks that have been unloaded since.
+    pub fn rewind(&mut self, delta: &GridDelta) -> EntityHashMap<Option<(UVec2, i32, GridLayers)>> {
+        if self.chunk_size.is_some() {
+            return EntityHashMap::default();
+        }
+        let pending = self
+            .journal
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default();
+        let journal = self.journal.take();
+        let layers: EntityHashMap<_> = delta.layers.iter().copied().collect();
+        let cells: Vec<(UVec2, SmallVec<[GridEntry; N]>)> = pending
+            .cells
+            .into_iter()
+            .chain(delta.cells.iter().map(|(cell, entities)| {
+                let entries = entities
+                    .iter()
+                    .map(|&(entity, floor)| GridEntry {
+                        entity,
+                        floor,
+                        layers: layers.get(&entity).copied().unwrap_or_default(),
+                    })
+                    .collect();
+                (*cell, entries)
+            }))
+            .collect();
+        let overflow = pending
+            .overflow
+            .into_iter()
+            .chain(delta.overflow.iter().copied());
+
+        let mut moved = EntityHashMap::default();
+        for (cell, _) in &cells {
+            for entry in self.entries(*cell) {
+                moved.insert(entry.entity, None);
+            }
+        }
+        for (entity, overflow) in overflow {
+            moved.entry(entity).or_insert(None);
+            if overflow {
+                self.insert_overflow(entity);
+            } else {
+                self.remove_overflow(entity);
+            }
+        }
+        // Later entries are older, so they win for cells changed more than once
+        for (cell, entries) in &cells {
+            if entries.is_empty() {
+                self.data.remove(cell);
+            } else {
+                self.data.insert(*cell, entries.clone());
+            }
+        }
+        for (cell, _) in &cells {
+            for entry in self.entries(*cell) {
+                moved.insert(entry.entity, Some((*cell, entry.floor, entry.layers)));
+            }
+        }
+        self.journal = journal;
+        moved
+    }
+
     /// Internal setter method for the grid's `dimensions`. Should only
     /// be done in response to `TransformGridEvent`.
     #[inline]
     pub(crate) fn set_dimensions(&mut self, value: impl Into<UVec2>) -> &mut Self {
         self.dimensions = value.into();
+        self.sync_affine();
         self
     }
 
@@ -83,6 +731,7 @@ impl<Marker: Component, const N: usize> Grid<Marker, N> {
     #[inline]
     pub(crate) fn set_spacing(&mut self, value: impl Into<Vec2>) -> &mut Self {
         self.spacing = value.into();
+        self.sync_affine();
         self
     }
 
@@ -91,24 +740,270 @@ impl<Marker: Component, const N: usize> Grid<Marker, N> {
     #[inline]
     pub(crate) fn set_anchor(&mut self, value: impl Into<Vec2>) -> &mut Self {
         self.anchor = value.into();
+        self.sync_affine();
+        self
+    }
+
+    /// Internal setter method for the grid's `frame`. Should only be done
+    /// when syncing with the `GlobalTransform` of the grid's entity.
+    #[inline]
+    pub(crate) fn set_frame(&mut self, value: Affine2) -> &mut Self {
+        self.frame = value;
+        self.sync_affine();
         self
     }
 
+    /// Internal setter method for the grid's `transform`. Should only
+    /// be done in response to `TransformGridEvent`.
+    #[inline]
+    pub(crate) fn set_transform(&mut self, value: Affine2) -> &mut Self {
+        self.transform = value;
+        self.sync_affine();
+        self
+    }
+
+    /// Update the cached conversions between cell coordinates and world space,
+    /// and remember the configuration they were computed from.
+    fn sync_affine(&mut self) {
+        let spacing = self.spacing;
+        let layout = match self.projection {
+            GridProjection::Orthogonal => Mat2::from_diagonal(spacing),
+            GridProjection::Isometric => Mat2::from_cols(
+                Vec2::new(spacing.x, spacing.y) * 0.5,
+                Vec2::new(-spacing.x, spacing.y) * 0.5,
+            ),
+        };
+        let cell_to_local = self.transform * Affine2::from_mat2_translation(layout, self.anchor);
+        self.cell_to_world = self.frame * cell_to_local;
+        self.world_to_local = self.frame.inverse();
+        self.local_to_layout = self.transform.inverse();
+        self.synced = Some(self.config());
+    }
+
+    /// Whether the configuration was edited through reflection since the cached
+    /// conversions were last updated. See `sync_config`.
+    #[inline]
+    pub(crate) fn is_config_stale(&self) -> bool {
+        self.synced != Some(self.config())
+    }
+
+    /// Update the cached conversions after the configuration was edited through
+    /// reflection. The grid has to be reset afterwards, since its contents may
+    /// no longer match the configuration.
+    #[inline]
+    pub(crate) fn sync_config(&mut self) {
+        self.hysteresis = self.hysteresis.max(0.);
+        self.chunk_size = self.chunk_size.map(|size| size.max(1));
+        self.sync_affine();
+    }
+
     pub fn reset(&mut self) {
+        if let Some(journal) = &mut self.journal {
+            for (&cell, entries) in &self.data {
+                journal.record(cell, entries);
+            }
+            for &entity in &self.overflow {
+                journal.record_overflow(entity, true);
+            }
+        }
         self.data = FxHashMap::default();
+        self.overflow = FxHashSet::default();
+        self.statics = Vec::new();
+        self.suspended = FxHashMap::default();
+        self.suspended_chunks = EntityHashMap::default();
     }
 
-    /// Insert an `entity` into the grid at `cell` coordinate. Updates
-    /// the `GridCell` component of the entity to reflect the change.
+    /// Add an `entity` outside the grid to the overflow list. Returns whether it
+    /// wasn't already in the list.
+    #[inline]
+    pub fn insert_overflow(&mut self, entity: Entity) -> bool {
+        let inserted = self.overflow.insert(entity);
+        if let Some(journal) = self.journal.as_mut().filter(|_| inserted) {
+            journal.record_overflow(entity, false);
+        }
+        inserted
+    }
+
+    /// Remove an `entity` from the overflow list. Returns whether it was in the list.
+    #[inline]
+    pub fn remove_overflow(&mut self, entity: Entity) -> bool {
+        let removed = self.overflow.remove(&entity);
+        if let Some(journal) = self.journal.as_mut().filter(|_| removed) {
+            journal.record_overflow(entity, true);
+        }
+        removed
+    }
+
+    /// Iterator for all the entities outside the grid. Only populated when using
+    /// `OutOfBoundsPolicy::Overflow`.
+    #[inline]
+    pub fn iter_overflow(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.overflow.iter().copied()
+    }
+
+    /// Iterator for every entity tracked by the grid, including the overflow list
+    /// and static entities.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.data
+            .values()
+            .flat_map(|entries| entries.iter().map(|entry| entry.entity))
+            .chain(self.iter_overflow())
+            .chain(self.iter_static())
+    }
+
+    /// Iterator for every entity marked `GridStatic`, ordered by row, column
+    /// and `Entity`.
+    #[inline]
+    pub fn iter_static(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.statics.iter().map(|(_, entry)| entry.entity)
+    }
+
+    /// Insert a static `entity` into the grid at `cell` coordinate on `floor` and
+    /// `layers`. Costs O(static entities), see `extend_static` for inserting many
+    /// at once. Static entities are not journaled.
+    pub fn insert_static(
+        &mut self,
+        entity: Entity,
+        cell: UVec2,
+        floor: i32,
+        layers: GridLayers,
+    ) -> Result<(), GridError> {
+        if !self.contains_cell(cell) {
+            return Err(GridError::OutOfBounds(cell.as_ivec2()));
+        }
+        let key = (cell.y, cell.x, entity);
+        let index = self
+            .statics
+            .partition_point(|(other, entry)| (other.y, other.x, entry.entity) < key);
+        self.statics.insert(
+            index,
+            (
+                cell,
+                GridEntry {
+                    entity,
+                    floor,
+                    layers,
+                },
+            ),
+        );
+        Ok(())
+    }
+
+    /// Insert static entities, each with its cell coordinate, floor and layers,
+    /// sorting the static layout once. Entities in cells outside the grid are
+    /// skipped.
+    pub fn extend_static(
+        &mut self,
+        entities: impl IntoIterator<Item = (Entity, UVec2, i32, GridLayers)>,
+    ) {
+        let dimensions = self.dimensions;
+        let len = self.statics.len();
+        self.statics.extend(
+            entities
+                .into_iter()
+                .filter(|(_, cell, _, _)| cell.cmplt(dimensions).all())
+                .map(|(entity, cell, floor, layers)| {
+                    (
+                        cell,
+                        GridEntry {
+                            entity,
+                            floor,
+                            layers,
+                        },
+                    )
+                }),
+        );
+        if self.statics.len() != len {
+            self.statics
+                .sort_unstable_by_key(|(cell, entry)| (cell.y, cell.x, entry.entity));
+        }
+    }
+
+    /// Remove a static `entity` located at `cell` coordinate from the grid.
+    pub fn remove_static(&mut self, entity: Entity, cell: UVec2) -> Result<(), GridError> {
+        let run = self.static_range(cell);
+        if run.is_empty() {
+            return Err(GridError::CellNotFound(cell));
+        }
+        let Some(pos) = self.statics[run.clone()]
+            .iter()
+            .position(|(_, entry)| entry.entity == entity)
+        else {
+            return Err(GridError::EntityNotFound(entity));
+        };
+        self.statics.remove(run.start + pos);
+        Ok(())
+    }
+
+    /// Indices of the static entities in `cell`.
+    #[inline]
+    fn static_range(&self, cell: UVec2) -> std::ops::Range<usize> {
+        let key = (cell.y, cell.x);
+        let start = self
+            .statics
+            .partition_point(|(other, _)| (other.y, other.x) < key);
+        let len = self.statics[start..].partition_point(|(other, _)| *other == cell);
+        start..start + len
+    }
+
+    /// Insert an `entity` into the grid at `cell` coordinate on floor `0`.
+    /// Updates the `GridCell` component of the entity to reflect the change.
     #[inline]
     pub fn insert(&mut self, entity: Entity, cell: UVec2) -> Result<(), GridError> {
+        self.insert_on_floor(entity, cell, 0)
+    }
+
+    /// Insert an `entity` into the grid at `cell` coordinate on `floor`.
+    #[inline]
+    pub fn insert_on_floor(
+        &mut self,
+        entity: Entity,
+        cell: UVec2,
+        floor: i32,
+    ) -> Result<(), GridError> {
+        self.insert_with_layers(entity, cell, floor, GridLayers::DEFAULT)
+    }
+
+    /// Insert an `entity` into the grid at `cell` coordinate on `floor` and `layers`.
+    #[inline]
+    pub fn insert_with_layers(
+        &mut self,
+        entity: Entity,
+        cell: UVec2,
+        floor: i32,
+        layers: GridLayers,
+    ) -> Result<(), GridError> {
         if !self.contains_cell(cell) {
             return Err(GridError::OutOfBounds(cell.as_ivec2()));
         }
-        self.data.entry(cell).or_default().push(entity);
+        self.push_entry(
+            cell,
+            GridEntry {
+                entity,
+                floor,
+                layers,
+            },
+        );
         Ok(())
     }
 
+    /// Add an `entry` to `cell`, keeping the cell sorted in deterministic mode.
+    #[inline]
+    fn push_entry(&mut self, cell: UVec2, entry: GridEntry) {
+        let deterministic = self.deterministic;
+        let entries = self.data.entry(cell).or_default();
+        if let Some(journal) = &mut self.journal {
+            journal.record(cell, entries);
+        }
+        if deterministic {
+            let index = entries.partition_point(|other| other.entity < entry.entity);
+            entries.insert(index, entry);
+        } else {
+            entries.push(entry);
+        }
+    }
+
     /// Insert an `entity` into the grid at `translation` world-space coordinate.
     /// Updates the `GridCell` component of the entity to reflect the change.
     #[inline]
@@ -118,15 +1013,88 @@ impl<Marker: Component, const N: usize> Grid<Marker, N> {
         translation: Vec3,
     ) -> Result<UVec2, GridError> {
         let cell = self.world_to_grid(translation)?;
-        self.data.entry(cell).or_default().push(entity);
+        let floor = self.world_to_floor(translation);
+        self.insert_on_floor(entity, cell, floor)?;
         Ok(cell)
     }
 
+    /// Iterator for all the entities in `cell`, on every floor. Dynamic entities
+    /// come before static ones.
     #[inline]
     pub fn get(&self, cell: UVec2) -> impl Iterator<Item = Entity> {
+        self.get_of_kind(cell, EntityKind::All)
+    }
+
+    /// Iterator for the entities of `kind` in `cell`, on every floor.
+    #[inline]
+    pub fn get_of_kind(&self, cell: UVec2, kind: EntityKind) -> impl Iterator<Item = Entity> {
+        self.entries_of_kind(cell, kind).map(|entry| entry.entity)
+    }
+
+    /// Iterator for the entities of `kind` in `cell` and the floors they are on.
+    #[inline]
+    pub fn get_of_kind_with_floors(
+        &self,
+        cell: UVec2,
+        kind: EntityKind,
+    ) -> impl Iterator<Item = (Entity, i32)> {
+        self.entries_of_kind(cell, kind)
+            .map(|entry| (entry.entity, entry.floor))
+    }
+
+    /// Iterator for all the entities in `cell` on a floor in `floors`. Use
+    /// `floor..=floor` for a single floor.
+    #[inline]
+    pub fn get_on_floors(
+        &self,
+        cell: UVec2,
+        floors: RangeInclusive<i32>,
+    ) -> impl Iterator<Item = Entity> {
+        self.entries_of_kind(cell, EntityKind::All)
+            .filter(move |entry| floors.contains(&entry.floor))
+            .map(|entry| entry.entity)
+    }
+
+    /// Iterator for all the entities in `cell` and the floors they are on.
+    #[inline]
+    pub fn get_with_floors(&self, cell: UVec2) -> impl Iterator<Item = (Entity, i32)> {
+        self.get_of_kind_with_floors(cell, EntityKind::All)
+    }
+
+    /// Iterator for all the entities in `cell` and their layers.
+    #[inline]
+    pub fn get_with_layers(&self, cell: UVec2) -> impl Iterator<Item = (Entity, GridLayers)> {
+        self.entries_of_kind(cell, EntityKind::All)
+            .map(|entry| (entry.entity, entry.layers))
+    }
+
+    /// Iterator for the entities in `cell` on a layer in `layers`.
+    #[inline]
+    pub fn get_in_layers(&self, cell: UVec2, layers: GridLayers) -> impl Iterator<Item = Entity> {
+        self.entries_of_kind(cell, EntityKind::All)
+            .filter(move |entry| entry.layers.intersects(layers))
+            .map(|entry| entry.entity)
+    }
+
+    #[inline]
+    fn entries(&self, cell: UVec2) -> &[GridEntry] {
         self.data
             .get(&cell)
-            .map_or([].iter().copied(), |v| v.iter().copied())
+            .map_or(&[], |entries| entries.as_slice())
+    }
+
+    /// Entries of `kind` in `cell`, dynamic ones first.
+    #[inline]
+    fn entries_of_kind(&self, cell: UVec2, kind: EntityKind) -> impl Iterator<Item = &GridEntry> {
+        let dynamic = match kind {
+            EntityKind::Static => &[],
+            _ => self.entries(cell),
+        };
+        let statics = match kind {
+            EntityKind::Dynamic => &[],
+            _ => &self.statics[self.static_range(cell)],
+        };
+        dynamic.iter().chain(statics.iter().map(|(_, entry)| entry))
     }
 
     /// Iterator for all the entities in grid cells neighboring `cell`.
@@ -142,21 +1110,62 @@ impl<Marker: Component, const N: usize> Grid<Marker, N> {
         self.iter_neighbors(cell).chain(self.get(cell))
     }
 
+    /// Iterator for the entities of `kind` in grid cells neighboring `cell`.
     #[inline]
-    fn remove_from_grid(&mut self, entity: Entity, cell: UVec2) -> Result<(), GridError> {
-        if let Some(entities) = self.data.get_mut(&cell) {
-            if let Some(pos) = entities.iter().position(|&e| e == entity) {
-                entities.swap_remove(pos);
-                if entities.is_empty() {
-                    self.data.remove(&cell);
-                }
-            } else {
-                return Err(GridError::EntityNotFound(entity));
-            }
-        } else {
+    pub fn iter_neighbors_of_kind(
+        &self,
+        cell: UVec2,
+        kind: EntityKind,
+    ) -> impl Iterator<Item = Entity> + '_ {
+        self.get_cell_neighbors(cell)
+            .flat_map(move |neighbor_cell| self.get_of_kind(neighbor_cell, kind))
+    }
+
+    /// Iterator for all the entities on a floor in `floors` in grid cells
+    /// neighboring `cell`.
+    #[inline]
+    pub fn iter_neighbors_on_floors(
+        &self,
+        cell: UVec2,
+        floors: RangeInclusive<i32>,
+    ) -> impl Iterator<Item = Entity> + '_ {
+        self.get_cell_neighbors(cell)
+            .flat_map(move |neighbor_cell| self.get_on_floors(neighbor_cell, floors.clone()))
+    }
+
+    /// Iterator for the entities on a layer in `layers` in grid cells
+    /// neighboring `cell`.
+    #[inline]
+    pub fn iter_neighbors_in_layers(
+        &self,
+        cell: UVec2,
+        layers: GridLayers,
+    ) -> impl Iterator<Item = Entity> + '_ {
+        self.get_cell_neighbors(cell)
+            .flat_map(move |neighbor_cell| self.get_in_layers(neighbor_cell, layers))
+    }
+
+    #[inline]
+    fn remove_from_grid(&mut self, entity: Entity, cell: UVec2) -> Result<GridEntry, GridError> {
+        let Some(entries) = self.data.get_mut(&cell) else {
             return Err(GridError::CellNotFound(cell));
+        };
+        let Some(pos) = entries.iter().position(|entry| entry.entity == entity) else {
+            return Err(GridError::EntityNotFound(entity));
+        };
+        if let Some(journal) = &mut self.journal {
+            journal.record(cell, entries);
         }
-        Ok(())
+        // Removing in order keeps deterministic cells sorted
+        let entry = if self.deterministic {
+            entries.remove(pos)
+        } else {
+            entries.swap_remove(pos)
+        };
+        if entries.is_empty() {
+            self.data.remove(&cell);
+        }
+        Ok(entry)
     }
 
     /// Remove an `entity` located at `cell` coordinate from the grid. Updates
@@ -168,8 +1177,8 @@ impl<Marker: Component, const N: usize> Grid<Marker, N> {
     }
 
     /// Change the grid cell coordinate of an `entity` from `current_cell` to
-    /// `new_cell`. Updates the `GridCell` component of the entity to reflect
-    /// the change.
+    /// `new_cell`, keeping its floor. Updates the `GridCell` component of the
+    /// entity to reflect the change.
     #[inline]
     pub fn update(
         &mut self,
@@ -181,12 +1190,70 @@ impl<Marker: Component, const N: usize> Grid<Marker, N> {
             return Err(GridError::OutOfBounds(new_cell.as_ivec2()));
         }
         // Remove from current cell
-        self.remove_from_grid(entity, current_cell)?;
+        let entry = self.remove_from_grid(entity, current_cell)?;
         // Add to new cell
-        self.data.entry(new_cell).or_default().push(entity);
+        self.push_entry(new_cell, entry);
+        Ok(())
+    }
+
+    /// Move an `entity` from `current_cell` to `new_cell` and `floor`, keeping
+    /// its layers. The cells may be the same to only change floors.
+    #[inline]
+    pub fn update_on_floor(
+        &mut self,
+        entity: Entity,
+        current_cell: UVec2,
+        new_cell: UVec2,
+        floor: i32,
+    ) -> Result<(), GridError> {
+        if !self.contains_cell(new_cell) {
+            return Err(GridError::OutOfBounds(new_cell.as_ivec2()));
+        }
+        let entry = self.remove_from_grid(entity, current_cell)?;
+        self.push_entry(new_cell, GridEntry { floor, ..entry });
+        Ok(())
+    }
+
+    /// Move an `entity` from `current_cell` to `new_cell`, `floor` and `layers`.
+    /// The cells may be the same to only change floors or layers.
+    #[inline]
+    pub fn update_with_layers(
+        &mut self,
+        entity: Entity,
+        current_cell: UVec2,
+        new_cell: UVec2,
+        floor: i32,
+        layers: GridLayers,
+    ) -> Result<(), GridError> {
+        if !self.contains_cell(new_cell) {
+            return Err(GridError::OutOfBounds(new_cell.as_ivec2()));
+        }
+        self.remove_from_grid(entity, current_cell)?;
+        self.push_entry(
+            new_cell,
+            GridEntry {
+                entity,
+                floor,
+                layers,
+            },
+        );
         Ok(())
     }
 
+    /// The floor a `translation` in world space is on with `FloorMode::Band`.
+    /// Always `0` for the other floor modes.
+    #[inline]
+    pub fn world_to_floor(&self, translation: Vec3) -> i32 {
+        match self.floor_mode {
+            FloorMode::Band { height } if height > 0. => {
+                let floor = (translation.dot(self.plane.normal()) / height).floor();
+                // `as` saturates infinite depths, NaN depths go to floor `0`
+                if floor.is_nan() { 0 } else { floor as i32 }
+            }
+            _ => 0,
+        }
+    }
+
     /// Return whether a `cell` coordinate is inside the grid. Cell coordinates
     /// have a minimum at (0,0) and an exclusive maximum at the grid's `dimensions`.
     /// Cell coordinates will always be non-negative because they are relative to
@@ -196,26 +1263,496 @@ impl<Marker: Component, const N: usize> Grid<Marker, N> {
         cell.cmplt(self.dimensions).all()
     }
 
+    /// Convert a `translation` in world space to continuous cell coordinates,
+    /// where cell `(x, y)` covers `x..x + 1` and `y..y + 1`.
+    #[inline]
+    pub fn world_to_cell(&self, translation: Vec3) -> Vec2 {
+        self.plane_to_cell(self.plane.project(translation))
+    }
+
+    /// Convert a `translation` relative to the grid's entity to continuous cell
+    /// coordinates. The same as `world_to_cell` for grids without a `frame`.
+    #[inline]
+    pub fn local_to_cell(&self, translation: Vec3) -> Vec2 {
+        self.layout_to_cell(
+            self.local_to_layout
+                .transform_point2(self.plane.project(translation)),
+        )
+    }
+
+    /// Convert a `position` on the grid's `plane` to continuous cell coordinates.
+    #[inline]
+    fn plane_to_cell(&self, position: Vec2) -> Vec2 {
+        let local = self.world_to_local.transform_point2(position);
+        self.layout_to_cell(self.local_to_layout.transform_point2(local))
+    }
+
+    /// Convert a `position` in the grid's layout, before its `transform`, to
+    /// continuous cell coordinates. Divides by `spacing` rather than multiplying
+    /// by its reciprocal, which isn't exact for most spacings.
+    #[inline]
+    fn layout_to_cell(&self, position: Vec2) -> Vec2 {
+        let position = position - self.anchor;
+        match self.projection {
+            GridProjection::Orthogonal => position / self.spacing,
+            GridProjection::Isometric => {
+                let scaled = position / (self.spacing * 0.5);
+                Vec2::new(scaled.y + scaled.x, scaled.y - scaled.x) * 0.5
+            }
+        }
+    }
+
+    /// Convert continuous cell coordinates to a position on the grid's `plane`.
+    #[inline]
+    pub fn cell_to_world(&self, position: Vec2) -> Vec2 {
+        self.cell_to_world.transform_point2(position)
+    }
+
+    /// Convert continuous cell coordinates to a world-space position on the
+    /// grid's `plane` through the origin.
+    #[inline]
+    pub fn cell_to_world_3d(&self, position: Vec2) -> Vec3 {
+        self.plane.unproject(self.cell_to_world(position), 0.)
+    }
+
+    /// Bounding box in continuous cell coordinates of `rect` on the grid's `plane`.
+    #[inline]
+    pub fn world_rect_to_cells(&self, rect: Rect) -> Rect {
+        let [first, corners @ ..] = [
+            rect.min,
+            Vec2::new(rect.max.x, rect.min.y),
+            rect.max,
+            Vec2::new(rect.min.x, rect.max.y),
+        ]
+        .map(|corner| self.plane_to_cell(corner));
+        corners
+            .into_iter()
+            .fold(Rect::from_corners(first, first), |cells, corner| {
+                cells.union_point(corner)
+            })
+    }
+
+    /// Corners of `cell` on the grid's `plane`, in order around the cell starting from
+    /// the corner closest to cell `(0, 0)`.
+    #[inline]
+    pub fn cell_corners(&self, cell: UVec2) -> [Vec2; 4] {
+        let min = cell.as_vec2();
+        let max = min + Vec2::ONE;
+        [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)]
+            .map(|corner| self.cell_to_world(corner))
+    }
+
     /// Convert a `translation` in world space to a grid cell coordinate.
     #[inline]
     pub fn world_to_grid(&self, translation: Vec3) -> Result<UVec2, GridError> {
-        let cell: IVec2 = ((translation.xy() - self.anchor) / self.spacing)
-            .floor()
-            .as_ivec2();
+        self.cell_to_grid(self.world_to_cell(translation), translation)
+    }
+
+    /// Convert a `translation` relative to the grid's entity to a grid cell
+    /// coordinate. Used to place entities, so grids on moving entities keep their
+    /// members in place. The same as `world_to_grid` for grids without a `frame`.
+    #[inline]
+    pub fn local_to_grid(&self, translation: Vec3) -> Result<UVec2, GridError> {
+        self.cell_to_grid(self.local_to_cell(translation), translation)
+    }
+
+    /// Grid cell coordinate containing the continuous cell coordinate `position`
+    /// of `translation`.
+    #[inline]
+    fn cell_to_grid(&self, position: Vec2, translation: Vec3) -> Result<UVec2, GridError> {
+        if !position.is_finite() {
+            return Err(GridError::NonFinite(translation));
+        }
+        let cell: IVec2 = position.floor().as_ivec2();
         if cell.cmpge(self.dimensions.as_ivec2()).any() || cell.cmplt(IVec2::ZERO).any() {
             return Err(GridError::OutOfBounds(cell));
         }
         Ok(cell.as_uvec2())
     }
 
+    /// Convert a `translation` in world space to a grid cell coordinate for an
+    /// entity currently in `current_cell`. The entity stays in `current_cell` until
+    /// it moves further than the grid's `hysteresis` margin past its borders, which
+    /// also applies to leaving the grid.
+    #[inline]
+    pub fn world_to_grid_with_hysteresis(
+        &self,
+        translation: Vec3,
+        current_cell: UVec2,
+    ) -> Result<UVec2, GridError> {
+        let position = self.world_to_cell(translation);
+        self.cell_to_grid_with_hysteresis(position, translation, current_cell)
+    }
+
+    /// Like `world_to_grid_with_hysteresis`, for a `translation` relative to the
+    /// grid's entity. See `local_to_grid`.
+    #[inline]
+    pub fn local_to_grid_with_hysteresis(
+        &self,
+        translation: Vec3,
+        current_cell: UVec2,
+    ) -> Result<UVec2, GridError> {
+        let position = self.local_to_cell(translation);
+        self.cell_to_grid_with_hysteresis(position, translation, current_cell)
+    }
+
+    #[inline]
+    fn cell_to_grid_with_hysteresis(
+        &self,
+        position: Vec2,
+        translation: Vec3,
+        current_cell: UVec2,
+    ) -> Result<UVec2, GridError> {
+        if self.hysteresis > 0. && self.contains_cell(current_cell) {
+            let min = current_cell.as_vec2() - self.hysteresis;
+            let max = min + 1. + 2. * self.hysteresis;
+            if position.cmpge(min).all() && position.cmplt(max).all() {
+                return Ok(current_cell);
+            }
+        }
+        self.cell_to_grid(position, translation)
+    }
+
+    /// Clamp a possibly out-of-bounds `cell` coordinate to the closest cell
+    /// on the edge of the grid.
+    #[inline]
+    pub fn clamp_cell(&self, cell: IVec2) -> UVec2 {
+        cell.clamp(
+            IVec2::ZERO,
+            self.dimensions.saturating_sub(UVec2::ONE).as_ivec2(),
+        )
+        .as_uvec2()
+    }
+
+    /// Coordinate of the chunk containing `cell`. Every cell is in chunk `(0, 0)`
+    /// when `chunk_size` isn't set.
+    #[inline]
+    pub fn cell_to_chunk(&self, cell: UVec2) -> UVec2 {
+        self.chunk_size.map_or(UVec2::ZERO, |size| cell / size)
+    }
+
+    /// Cells covered by `chunk`, clipped to the grid. See `cell_to_chunk`.
+    pub fn chunk_cells(&self, chunk: UVec2) -> impl Iterator<Item = UVec2> {
+        let (min, max) = match self.chunk_size {
+            Some(size) => (chunk * size, ((chunk + 1) * size).min(self.dimensions)),
+            None => (UVec2::ZERO, self.dimensions),
+        };
+        (min.y..max.y).flat_map(move |y| (min.x..max.x).map(move |x| UVec2::new(x, y)))
+    }
+
+    /// Whether `chunk` is loaded. Always true when `chunk_size` isn't set.
+    #[inline]
+    pub fn is_chunk_loaded(&self, chunk: UVec2) -> bool {
+        self.chunk_size.is_none() || self.loaded_chunks.contains(&chunk)
+    }
+
+    /// Whether the chunk containing `cell` is loaded. See `is_chunk_loaded`.
+    #[inline]
+    pub fn is_cell_loaded(&self, cell: UVec2) -> bool {
+        self.is_chunk_loaded(self.cell_to_chunk(cell))
+    }
+
+    /// Iterator for the loaded chunks. Empty when `chunk_size` isn't set.
+    #[inline]
+    pub fn iter_loaded_chunks(&self) -> impl Iterator<Item = UVec2> + '_ {
+        self.loaded_chunks.iter().copied()
+    }
+
+    /// Internal setter method for the grid's loaded chunks. Should only be done
+    /// in response to `ChunkAnchor`s moving. The entities in the chunks that
+    /// loaded or unloaded are re-evaluated on the next update.
+    pub(crate) fn set_loaded_chunks(&mut self, chunks: FxHashSet<UVec2>) {
+        self.deactivated_chunks.extend(
+            self.loaded_chunks
+                .iter()
+                .filter(|chunk| !chunks.contains(chunk)),
+        );
+        self.activated_chunks.extend(
+            chunks
+                .iter()
+                .filter(|chunk| !self.loaded_chunks.contains(chunk)),
+        );
+        self.loaded_chunks = chunks;
+    }
+
+    /// The chunks that unloaded and the ones that loaded since the last call.
+    #[inline]
+    pub(crate) fn take_chunk_changes(&mut self) -> (Vec<UVec2>, Vec<UVec2>) {
+        (
+            std::mem::take(&mut self.deactivated_chunks),
+            std::mem::take(&mut self.activated_chunks),
+        )
+    }
+
+    /// Iterator for the entities left out of the grid until `chunk` loads.
+    #[inline]
+    pub fn iter_suspended(&self, chunk: UVec2) -> impl Iterator<Item = Entity> + '_ {
+        self.suspended.get(&chunk).into_iter().flatten().copied()
+    }
+
+    /// Internal method to leave `entity` out of the grid until `chunk` loads.
+    /// Should only be done when the entity isn't in a cell.
+    pub(crate) fn suspend(&mut self, entity: Entity, chunk: UVec2) {
+        match self.suspended_chunks.insert(entity, chunk) {
+            Some(previous) if previous == chunk => return,
+            Some(previous) => self.remove_suspended(entity, previous),
+            None => (),
+        }
+        self.suspended.entry(chunk).or_default().insert(entity);
+    }
+
+    /// Internal method to stop waiting for the chunk of a suspended `entity`,
+    /// e.g. because it moved into a loaded chunk.
+    #[inline]
+    pub(crate) fn unsuspend(&mut self, entity: Entity) {
+        if let Some(chunk) = self.suspended_chunks.remove(&entity) {
+            self.remove_suspended(entity, chunk);
+        }
+    }
+
+    fn remove_suspended(&mut self, entity: Entity, chunk: UVec2) {
+        if let Some(entities) = self.suspended.get_mut(&chunk) {
+            entities.remove(&entity);
+            if entities.is_empty() {
+                self.suspended.remove(&chunk);
+            }
+        }
+    }
+
+    /// Internal method to take the entities out of the cells of `chunk` after it
+    /// unloaded, suspending them until it loads again. Returns every entity with
+    /// the cell it was in.
+    pub(crate) fn suspend_chunk(&mut self, chunk: UVec2) -> Vec<(Entity, UVec2)> {
+        let mut removed = Vec::new();
+        let cells: Vec<_> = self.chunk_cells(chunk).collect();
+        for cell in cells {
+            if let Some(entries) = self.data.remove(&cell) {
+                if let Some(journal) = &mut self.journal {
+                    journal.record(cell, &entries);
+                }
+                removed.extend(entries.iter().map(|entry| (entry.entity, cell)));
+            }
+            let run = self.static_range(cell);
+            removed.extend(
+                self.statics
+                    .drain(run)
+                    .map(|(cell, entry)| (entry.entity, cell)),
+            );
+        }
+        for &(entity, _) in &removed {
+            self.suspend(entity, chunk);
+        }
+        removed
+    }
+
+    /// Internal method to take the entities suspended until `chunk` loaded, so
+    /// they can be placed in the grid again.
+    pub(crate) fn take_suspended(&mut self, chunk: UVec2) -> EntityHashSet {
+        let entities = self.suspended.remove(&chunk).unwrap_or_default();
+        for entity in &entities {
+            self.suspended_chunks.remove(entity);
+        }
+        entities
+    }
+
+    /// Iterator for all the entities in grid cells within `radius` cells of `cell`,
+    /// including `cell` itself. Distance is measured in cells along either axis.
+    #[inline]
+    pub fn iter_radius(&self, cell: UVec2, radius: u32) -> impl Iterator<Item = Entity> + '_ {
+        self.get_cells_in_radius(cell, radius)
+            .flat_map(move |cell| self.get(cell))
+    }
+
+    /// Iterator for the entities of `kind` in grid cells within `radius` cells of
+    /// `cell`. See `iter_radius`.
+    #[inline]
+    pub fn iter_radius_of_kind(
+        &self,
+        cell: UVec2,
+        radius: u32,
+        kind: EntityKind,
+    ) -> impl Iterator<Item = Entity> + '_ {
+        self.get_cells_in_radius(cell, radius)
+            .flat_map(move |cell| self.get_of_kind(cell, kind))
+    }
+
+    /// Iterator for the entities on a layer in `layers` in grid cells within
+    /// `radius` cells of `cell`. See `iter_radius`.
+    #[inline]
+    pub fn iter_radius_in_layers(
+        &self,
+        cell: UVec2,
+        radius: u32,
+        layers: GridLayers,
+    ) -> impl Iterator<Item = Entity> + '_ {
+        self.get_cells_in_radius(cell, radius)
+            .flat_map(move |cell| self.get_in_layers(cell, layers))
+    }
+
+    /// Iterator for all the entities on a floor in `floors` in grid cells within
+    /// `radius` cells of `cell`. See `iter_radius`.
+    #[inline]
+    pub fn iter_radius_on_floors(
+        &self,
+        cell: UVec2,
+        radius: u32,
+        floors: RangeInclusive<i32>,
+    ) -> impl Iterator<Item = Entity> + '_ {
+        self.get_cells_in_radius(cell, radius)
+            .flat_map(move |cell| self.get_on_floors(cell, floors.clone()))
+    }
+
+    /// Return an iterator over all valid cell coordinates within `radius` cells
+    /// of `cell`, including `cell` itself.
+    #[inline]
+    pub fn get_cells_in_radius(&self, cell: UVec2, radius: u32) -> impl Iterator<Item = UVec2> {
+        let max = self.dimensions.saturating_sub(UVec2::ONE);
+        let min = cell.saturating_sub(UVec2::splat(radius)).min(max);
+        let max = cell.saturating_add(UVec2::splat(radius)).min(max);
+        (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| UVec2::new(x, y)))
+    }
+
+    /// Iterator for all the entities in grid cells overlapping the world-space `rect`.
+    /// For rotated, sheared or isometric grids, cells overlapping the bounding box
+    /// of `rect` in cell coordinates are included.
+    #[inline]
+    pub fn iter_rect(&self, rect: Rect) -> impl Iterator<Item = Entity> + '_ {
+        self.get_cells_in_rect(rect)
+            .flat_map(move |cell| self.get(cell))
+    }
+
+    /// Iterator for the entities of `kind` in grid cells overlapping the
+    /// world-space `rect`. See `iter_rect`.
+    #[inline]
+    pub fn iter_rect_of_kind(
+        &self,
+        rect: Rect,
+        kind: EntityKind,
+    ) -> impl Iterator<Item = Entity> + '_ {
+        self.get_cells_in_rect(rect)
+            .flat_map(move |cell| self.get_of_kind(cell, kind))
+    }
+
+    /// Iterator for the entities on a layer in `layers` in grid cells overlapping
+    /// the world-space `rect`. See `iter_rect`.
+    #[inline]
+    pub fn iter_rect_in_layers(
+        &self,
+        rect: Rect,
+        layers: GridLayers,
+    ) -> impl Iterator<Item = Entity> + '_ {
+        self.get_cells_in_rect(rect)
+            .flat_map(move |cell| self.get_in_layers(cell, layers))
+    }
+
+    /// Iterator for all the entities on a floor in `floors` in grid cells
+    /// overlapping the world-space `rect`. See `iter_rect`.
+    #[inline]
+    pub fn iter_rect_on_floors(
+        &self,
+        rect: Rect,
+        floors: RangeInclusive<i32>,
+    ) -> impl Iterator<Item = Entity> + '_ {
+        self.get_cells_in_rect(rect)
+            .flat_map(move |cell| self.get_on_floors(cell, floors.clone()))
+    }
+
+    /// Return an iterator over all valid cell coordinates overlapping the
+    /// world-space `rect`. See `iter_rect`.
+    #[inline]
+    pub fn get_cells_in_rect(&self, rect: Rect) -> impl Iterator<Item = UVec2> {
+        let cells = self.world_rect_to_cells(rect);
+        let outside =
+            cells.max.cmplt(Vec2::ZERO).any() || cells.min.cmpge(self.dimensions.as_vec2()).any();
+        let (min, max) = if outside {
+            // Empty range
+            (UVec2::ONE, UVec2::ZERO)
+        } else {
+            (
+                self.clamp_cell(cells.min.floor().as_ivec2()),
+                self.clamp_cell(cells.max.floor().as_ivec2()),
+            )
+        };
+        (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| UVec2::new(x, y)))
+    }
+
+    /// Iterator over every occupied cell and the number of entities in it.
+    /// The order is arbitrary, see `iter_occupancy_sorted` for a stable one.
+    #[inline]
+    pub fn iter_occupancy(&self) -> impl Iterator<Item = (UVec2, usize)> + '_ {
+        let statics = self
+            .statics
+            .chunk_by(|(a, _), (b, _)| a == b)
+            .filter(|run| !self.data.contains_key(&run[0].0))
+            .map(|run| (run[0].0, run.len()));
+        self.data
+            .iter()
+            .map(|(&cell, entities)| (cell, entities.len() + self.static_range(cell).len()))
+            .chain(statics)
+    }
+
+    /// Iterator over every cell with dynamic entities and the number of them in
+    /// it. Only dynamic entities are stored inline, so a count above `N` means the
+    /// cell spilled onto the heap. The order is arbitrary.
+    #[inline]
+    pub fn iter_dynamic_occupancy(&self) -> impl Iterator<Item = (UVec2, usize)> + '_ {
+        self.data
+            .iter()
+            .map(|(&cell, entities)| (cell, entities.len()))
+    }
+
+    /// Iterator over every occupied cell and the number of entities in it,
+    /// ordered by row and then column.
+    pub fn iter_occupancy_sorted(&self) -> impl Iterator<Item = (UVec2, usize)> + '_ {
+        self.sorted_cells().into_iter().map(|cell| {
+            (
+                cell,
+                self.entries(cell).len() + self.static_range(cell).len(),
+            )
+        })
+    }
+
+    /// Iterator for every entity tracked by the grid in a stable order: cells
+    /// by row and then column, followed by the sorted overflow list. Within a
+    /// cell entities are in `Entity` order when the grid is `deterministic`,
+    /// and otherwise depends on the order they were inserted and removed in.
+    pub fn iter_sorted(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.sorted_cells()
+            .into_iter()
+            .flat_map(|cell| self.get(cell))
+            .chain(self.sorted_overflow())
+    }
+
+    /// Occupied cells ordered by row and then column.
+    fn sorted_cells(&self) -> Vec<UVec2> {
+        let mut cells: Vec<_> = self
+            .data
+            .keys()
+            .copied()
+            .chain(self.statics.iter().map(|&(cell, _)| cell))
+            .collect();
+        cells.sort_unstable_by_key(|cell| (cell.y, cell.x));
+        cells.dedup();
+        cells
+    }
+
+    /// Overflowing entities in `Entity` order.
+    fn sorted_overflow(&self) -> Vec<Entity> {
+        let mut overflow: Vec<_> = self.overflow.iter().copied().collect();
+        overflow.sort_unstable();
+        overflow
+    }
+
+    /// Convert a grid `cell` coordinate to the position of its center on the grid's `plane`.
+    #[inline]
+    pub fn grid_to_world(&self, cell: UVec2) -> Vec2 {
+        self.cell_to_world(cell.as_vec2() + 0.5)
+    }
+
     /// Return an iterator over all valid neighboring cell coordinates.
     #[inline]
     pub fn get_cell_neighbors(&self, cell: UVec2) -> GridCellIterator {
-        GridCellIterator {
-            cell: cell.as_ivec2(),
-            dimensions: self.dimensions,
-            pattern: 0,
-        }
+        GridCellIterator::new(cell, self.dimensions)
     }
 }
 
@@ -225,6 +1762,17 @@ pub struct GridCellIterator {
     pattern: u8,
 }
 
+impl GridCellIterator {
+    #[inline]
+    pub(crate) fn new(cell: UVec2, dimensions: UVec2) -> Self {
+        Self {
+            cell: cell.as_ivec2(),
+            dimensions,
+            pattern: 0,
+        }
+    }
+}
+
 impl Iterator for GridCellIterator {
     type Item = UVec2;
 
@@ -320,6 +1868,31 @@ mod tests {
         // Test out of bounds
         assert!(grid.world_to_grid(Vec3::new(-1.0, 0.0, 0.0)).is_err());
         assert!(grid.world_to_grid(Vec3::new(321.0, 0.0, 0.0)).is_err());
+
+        // Positions on a cell border land in the cell past it, even for spacings
+        // whose reciprocal isn't exact
+        let grid = grid.with_spacing(Vec2::splat(10.));
+        assert_eq!(
+            grid.world_to_grid(Vec3::new(10.0, 20.0, 0.0)).unwrap(),
+            UVec2::new(1, 2)
+        );
+        assert_eq!(
+            grid.world_to_grid(Vec3::new(20.0, 10.0, 0.0)).unwrap(),
+            UVec2::new(2, 1)
+        );
+        assert_eq!(
+            grid.local_to_grid(Vec3::new(10.0, 20.0, 0.0)).unwrap(),
+            UVec2::new(1, 2)
+        );
+        for spacing in [5., 7., 10.] {
+            let grid = Grid::<TestMarker>::default()
+                .with_dimensions(UVec2::new(10, 10))
+                .with_spacing(Vec2::splat(spacing));
+            for x in 0..10 {
+                let translation = Vec3::new(x as f32 * spacing, 0., 0.);
+                assert_eq!(grid.world_to_grid(translation).unwrap().x, x);
+            }
+        }
     }
 
     #[test]
@@ -343,6 +1916,642 @@ mod tests {
         assert!(grid.world_to_grid(Vec3::new(0.0, 16.0, 0.0)).is_err());
     }
 
+    #[test]
+    fn test_world_to_grid_with_hysteresis() {
+        let grid = Grid::<TestMarker>::default()
+            .with_dimensions(UVec2::new(10, 10))
+            .with_spacing(Vec2::splat(32.))
+            .with_hysteresis(0.25);
+        let cell = UVec2::new(1, 1);
+
+        // Within 8 units of the current cell's borders the entity stays put
+        assert_eq!(
+            grid.world_to_grid_with_hysteresis(Vec3::new(70.0, 40.0, 0.0), cell)
+                .unwrap(),
+            cell
+        );
+        assert_eq!(
+            grid.world_to_grid_with_hysteresis(Vec3::new(25.0, 40.0, 0.0), cell)
+                .unwrap(),
+            cell
+        );
+        // Past the margin it moves to the new cell
+        assert_eq!(
+            grid.world_to_grid_with_hysteresis(Vec3::new(73.0, 40.0, 0.0), cell)
+                .unwrap(),
+            UVec2::new(2, 1)
+        );
+
+        // The margin also applies to leaving the grid
+        let edge = UVec2::new(0, 0);
+        assert_eq!(
+            grid.world_to_grid_with_hysteresis(Vec3::new(-4.0, 10.0, 0.0), edge)
+                .unwrap(),
+            edge
+        );
+        assert!(
+            grid.world_to_grid_with_hysteresis(Vec3::new(-9.0, 10.0, 0.0), edge)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_world_to_grid_with_frame() {
+        let mut grid = Grid::<TestMarker>::default()
+            .with_dimensions(UVec2::new(10, 10))
+            .with_spacing(Vec2::splat(32.));
+        // Rotate the grid a quarter turn counter-clockwise around (100, 0)
+        grid.set_frame(Affine2::from_angle_translation(
+            std::f32::consts::FRAC_PI_2,
+            Vec2::new(100.0, 0.0),
+        ));
+
+        // Local (40, 8) ends up at world (92, 40)
+        assert_eq!(
+            grid.world_to_grid(Vec3::new(92.0, 40.0, 0.0)).unwrap(),
+            UVec2::new(1, 0)
+        );
+        assert!(grid.world_to_grid(Vec3::new(108.0, 40.0, 0.0)).is_err());
+        assert!(
+            grid.grid_to_world(UVec2::new(1, 0))
+                .abs_diff_eq(Vec2::new(84.0, 48.0), 1e-4)
+        );
+        // Positions relative to the grid's entity ignore the frame
+        assert_eq!(
+            grid.local_to_grid(Vec3::new(40.0, 8.0, 0.0)).unwrap(),
+            UVec2::new(1, 0)
+        );
+    }
+
+    #[test]
+    fn test_world_to_grid_with_transform() {
+        // A 45 degree diamond arena, sheared grids work the same way
+        let grid = Grid::<TestMarker>::default()
+            .with_dimensions(UVec2::new(10, 10))
+            .with_spacing(Vec2::splat(10.))
+            .with_transform(Affine2::from_angle(std::f32::consts::FRAC_PI_4));
+
+        // Straight up from the origin runs along the diagonal of the cells
+        assert_eq!(
+            grid.world_to_grid(Vec3::new(0.0, 20.0, 0.0)).unwrap(),
+            UVec2::new(1, 1)
+        );
+        assert!(grid.world_to_grid(Vec3::new(0.0, -1.0, 0.0)).is_err());
+        assert!(grid.world_to_grid(Vec3::new(1.0, 10.0, 0.0)).is_ok());
+        assert!(grid.world_to_grid(Vec3::new(-10.0, 1.0, 0.0)).is_err());
+
+        let corners = grid.cell_corners(UVec2::new(0, 0));
+        assert!(corners[2].abs_diff_eq(Vec2::new(0.0, 200f32.sqrt()), 1e-4));
+        assert!(
+            grid.grid_to_world(UVec2::new(0, 0))
+                .abs_diff_eq(Vec2::new(0.0, 50f32.sqrt()), 1e-4)
+        );
+    }
+
+    #[test]
+    fn test_xz_plane() {
+        let grid = Grid::<TestMarker>::default()
+            .with_dimensions(UVec2::new(10, 10))
+            .with_spacing(Vec2::splat(32.))
+            .with_plane(GridPlane::XZ);
+
+        // Height doesn't matter on the XZ plane
+        assert_eq!(
+            grid.world_to_grid(Vec3::new(40.0, 500.0, 70.0)).unwrap(),
+            UVec2::new(1, 2)
+        );
+        assert!(grid.world_to_grid(Vec3::new(40.0, 0.0, -1.0)).is_err());
+        assert_eq!(
+            grid.cell_to_world_3d(Vec2::new(1.0, 2.0)),
+            Vec3::new(32.0, 0.0, 64.0)
+        );
+
+        // A grid entity turned a quarter around the y axis
+        let affine = Affine3A::from_rotation_translation(
+            bevy::math::Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
+            Vec3::new(100.0, 5.0, 0.0),
+        );
+        let frame = GridPlane::XZ.project_affine(affine);
+        assert!(
+            frame
+                .transform_point2(Vec2::new(10.0, 0.0))
+                .abs_diff_eq(Vec2::new(100.0, -10.0), 1e-4)
+        );
+    }
+
+    #[test]
+    fn test_floors() {
+        let mut grid = Grid::<TestMarker>::default()
+            .with_dimensions(UVec2::new(10, 10))
+            .with_spacing(Vec2::splat(32.))
+            .with_floor_mode(FloorMode::Band { height: 10. });
+        let road = Entity::from_raw(1);
+        let bridge = Entity::from_raw(2);
+        let cell = UVec2::new(1, 1);
+
+        assert_eq!(grid.world_to_floor(Vec3::new(40.0, 40.0, 0.0)), 0);
+        assert_eq!(grid.world_to_floor(Vec3::new(40.0, 40.0, 15.0)), 1);
+        assert_eq!(grid.world_to_floor(Vec3::new(40.0, 40.0, -0.5)), -1);
+
+        grid.insert_on_floor(road, cell, 0).unwrap();
+        grid.insert_on_floor(bridge, cell, 1).unwrap();
+        assert_eq!(grid.get(cell).count(), 2);
+        assert_eq!(
+            grid.get_on_floors(cell, 1..=1).collect::<Vec<_>>(),
+            [bridge]
+        );
+        assert_eq!(
+            grid.iter_neighbors_on_floors(UVec2::new(2, 1), 0..=0)
+                .collect::<Vec<_>>(),
+            [road]
+        );
+
+        // Moving keeps the floor unless it is changed explicitly
+        grid.update(bridge, cell, UVec2::new(2, 1)).unwrap();
+        assert_eq!(
+            grid.get_with_floors(UVec2::new(2, 1)).collect::<Vec<_>>(),
+            [(bridge, 1)]
+        );
+        grid.update_on_floor(bridge, UVec2::new(2, 1), UVec2::new(2, 1), 0)
+            .unwrap();
+        assert_eq!(grid.iter_radius_on_floors(cell, 1, 0..=0).count(), 2);
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let mut grid = Grid::<TestMarker>::default()
+            .with_dimensions(UVec2::new(10, 10))
+            .with_spacing(Vec2::splat(32.))
+            .with_deterministic(true);
+        let [a, b, c, d] = [4, 1, 3, 2].map(Entity::from_raw);
+        let cell = UVec2::new(1, 1);
+
+        for entity in [a, b, c] {
+            grid.insert(entity, cell).unwrap();
+        }
+        assert_eq!(grid.get(cell).collect::<Vec<_>>(), [b, c, a]);
+        grid.remove(b, cell).unwrap();
+        assert_eq!(grid.get(cell).collect::<Vec<_>>(), [c, a]);
+        grid.insert(d, UVec2::new(2, 0)).unwrap();
+        grid.update(d, UVec2::new(2, 0), cell).unwrap();
+        assert_eq!(grid.get(cell).collect::<Vec<_>>(), [d, c, a]);
+
+        grid.insert(b, UVec2::new(5, 0)).unwrap();
+        grid.insert_overflow(Entity::from_raw(6));
+        grid.insert_overflow(Entity::from_raw(5));
+        assert_eq!(
+            grid.iter_occupancy_sorted().collect::<Vec<_>>(),
+            [(UVec2::new(5, 0), 1), (cell, 3)]
+        );
+        assert_eq!(
+            grid.iter_sorted().collect::<Vec<_>>(),
+            [b, d, c, a, Entity::from_raw(5), Entity::from_raw(6)]
+        );
+    }
+
+    #[test]
+    fn test_chunks() {
+        let mut grid = Grid::<TestMarker>::default()
+            .with_dimensions(UVec2::new(10, 10))
+            .with_chunk_size(Some(4));
+        assert_eq!(grid.cell_to_chunk(UVec2::new(5, 9)), UVec2::new(1, 2));
+        assert_eq!(grid.chunk_cells(UVec2::new(2, 2)).count(), 4);
+        assert_eq!(grid.chunk_cells(UVec2::new(0, 0)).count(), 16);
+        assert!(!grid.is_cell_loaded(UVec2::new(5, 9)));
+
+        grid.set_loaded_chunks([UVec2::new(1, 2)].into_iter().collect());
+        assert_eq!(grid.take_chunk_changes(), (vec![], vec![UVec2::new(1, 2)]));
+        assert_eq!(grid.take_chunk_changes(), (vec![], vec![]));
+        assert!(grid.is_cell_loaded(UVec2::new(5, 9)));
+        assert!(!grid.is_cell_loaded(UVec2::new(3, 9)));
+
+        let grid = grid.with_chunk_size(None);
+        assert!(grid.is_cell_loaded(UVec2::new(3, 9)));
+        assert_eq!(grid.chunk_cells(UVec2::ZERO).count(), 100);
+    }
+
+    #[test]
+    fn test_statics() {
+        let mut grid = Grid::<TestMarker>::default()
+            .with_dimensions(UVec2::new(10, 10))
+            .with_spacing(Vec2::splat(32.));
+        let [tree, wall, rock, player] = [4, 2, 3, 1].map(Entity::from_raw);
+        let cell = UVec2::new(2, 2);
+        let layers = GridLayers::DEFAULT;
+        grid.extend_static([
+            (tree, cell, 0, layers),
+            (wall, UVec2::new(3, 2), 0, layers),
+            (rock, UVec2::new(20, 0), 0, layers),
+        ]);
+        grid.insert_static(rock, cell, 1, layers).unwrap();
+        grid.insert(player, cell).unwrap();
+
+        assert_eq!(grid.get(cell).collect::<Vec<_>>(), [player, rock, tree]);
+        assert_eq!(
+            grid.get_of_kind(cell, EntityKind::Static)
+                .collect::<Vec<_>>(),
+            [rock, tree]
+        );
+        assert_eq!(
+            grid.iter_radius_of_kind(cell, 1, EntityKind::Dynamic)
+                .collect::<Vec<_>>(),
+            [player]
+        );
+        assert_eq!(grid.iter_neighbors(UVec2::new(3, 3)).count(), 4);
+        assert_eq!(grid.get_on_floors(cell, 1..=1).collect::<Vec<_>>(), [rock]);
+        assert_eq!(grid.iter_static().collect::<Vec<_>>(), [rock, tree, wall]);
+        let mut occupancy: Vec<_> = grid.iter_occupancy().collect();
+        occupancy.sort_unstable_by_key(|&(cell, _)| (cell.y, cell.x));
+        assert_eq!(occupancy, [(cell, 3), (UVec2::new(3, 2), 1)]);
+        assert_eq!(grid.iter().count(), 4);
+
+        let snapshot = grid.snapshot();
+        assert_eq!(snapshot.cells, [(cell, vec![(player, 0)])]);
+        assert_eq!(snapshot.statics.len(), 2);
+
+        grid.remove_static(rock, cell).unwrap();
+        assert_eq!(
+            grid.remove_static(rock, cell),
+            Err(GridError::EntityNotFound(rock))
+        );
+        grid.restore(&snapshot);
+        assert_eq!(grid.snapshot(), snapshot);
+    }
+
+    #[test]
+    fn test_layers() {
+        let mut grid = Grid::<TestMarker>::default()
+            .with_dimensions(UVec2::new(10, 10))
+            .with_spacing(Vec2::splat(32.))
+            .with_journal(true);
+        let [ally, enemy, pickup, crate_] = [1, 2, 3, 4].map(Entity::from_raw);
+        let [allies, enemies, pickups] = [0, 1, 2].map(GridLayers::layer);
+        let cell = UVec2::new(2, 2);
+        grid.insert(ally, cell).unwrap();
+        grid.insert_with_layers(enemy, UVec2::new(3, 2), 0, enemies)
+            .unwrap();
+        grid.insert_with_layers(pickup, UVec2::new(3, 3), 0, pickups)
+            .unwrap();
+        grid.insert_static(crate_, UVec2::new(1, 1), 0, pickups | enemies)
+            .unwrap();
+
+        assert_eq!(grid.get_in_layers(cell, allies).collect::<Vec<_>>(), [ally]);
+        assert_eq!(grid.get_in_layers(cell, enemies).count(), 0);
+        let mut near: Vec<_> = grid.iter_neighbors_in_layers(cell, enemies).collect();
+        near.sort_unstable();
+        assert_eq!(near, [enemy, crate_]);
+        assert_eq!(
+            grid.iter_radius_in_layers(cell, 1, pickups | allies)
+                .count(),
+            3
+        );
+        assert_eq!(
+            grid.iter_rect_in_layers(Rect::new(64., 64., 95., 95.), GridLayers::ALL)
+                .collect::<Vec<_>>(),
+            [ally]
+        );
+        assert_eq!(
+            grid.iter_radius_in_layers(cell, 9, GridLayers::NONE)
+                .count(),
+            0
+        );
+
+        // Moving an entity keeps its layers unless they are given
+        grid.checkpoint();
+        grid.update(enemy, UVec2::new(3, 2), cell).unwrap();
+        assert_eq!(
+            grid.get_in_layers(cell, enemies).collect::<Vec<_>>(),
+            [enemy]
+        );
+        grid.update_with_layers(ally, cell, cell, 0, enemies)
+            .unwrap();
+        assert_eq!(grid.get_in_layers(cell, allies).count(), 0);
+        let delta = grid.checkpoint();
+        assert_eq!(delta.layers, [(enemy, enemies)]);
+
+        let snapshot = grid.snapshot();
+        assert_eq!(
+            snapshot.layers,
+            [
+                (ally, enemies),
+                (enemy, enemies),
+                (pickup, pickups),
+                (crate_, pickups | enemies)
+            ]
+        );
+        let moved = grid.rewind(&delta);
+        assert_eq!(moved[&ally], Some((cell, 0, allies)));
+        assert_eq!(moved[&enemy], Some((UVec2::new(3, 2), 0, enemies)));
+        grid.restore(&snapshot);
+        assert_eq!(grid.snapshot(), snapshot);
+    }
+
+    #[test]
+    fn test_journal() {
+        let mut grid = Grid::<TestMarker>::default()
+            .with_dimensions(UVec2::new(10, 10))
+            .with_spacing(Vec2::splat(32.))
+            .with_journal(true);
+        let a = Entity::from_raw(1);
+        let b = Entity::from_raw(2);
+        grid.insert(a, UVec2::new(1, 1)).unwrap();
+        grid.insert_on_floor(b, UVec2::new(1, 1), 1).unwrap();
+        let first = grid.checkpoint();
+        assert_eq!(first.cells, [(UVec2::new(1, 1), vec![])]);
+        let saved = grid.snapshot();
+
+        grid.update(a, UVec2::new(1, 1), UVec2::new(2, 1)).unwrap();
+        grid.update(a, UVec2::new(2, 1), UVec2::new(3, 1)).unwrap();
+        let second = grid.checkpoint();
+        assert_eq!(second.cells.len(), 3);
+        assert!(grid.checkpoint().is_empty());
+
+        // Changes since the last checkpoint are undone along with the delta
+        grid.remove(b, UVec2::new(1, 1)).unwrap();
+        grid.insert_overflow(b);
+        let moved = grid.rewind(&second);
+        assert_eq!(grid.snapshot(), saved);
+        assert_eq!(moved[&a], Some((UVec2::new(1, 1), 0, GridLayers::DEFAULT)));
+        assert_eq!(moved[&b], Some((UVec2::new(1, 1), 1, GridLayers::DEFAULT)));
+        assert!(grid.checkpoint().is_empty());
+
+        let moved = grid.rewind(&first);
+        assert_eq!(grid.iter().count(), 0);
+        assert_eq!(moved[&a], None);
+    }
+
+    #[test]
+    fn test_rewind_chunked() {
+        let mut grid = Grid::<TestMarker>::default()
+            .with_dimensions(UVec2::new(10, 10))
+            .with_spacing(Vec2::splat(32.))
+            .with_chunk_size(Some(4))
+            .with_journal(true);
+        grid.set_loaded_chunks([UVec2::ZERO, UVec2::new(1, 0)].into_iter().collect());
+        let a = Entity::from_raw(1);
+        grid.insert(a, UVec2::new(1, 1)).unwrap();
+        grid.checkpoint();
+        grid.update(a, UVec2::new(1, 1), UVec2::new(5, 1)).unwrap();
+        let delta = grid.checkpoint();
+        let saved = grid.snapshot();
+
+        // Rewinding could put `a` back into a chunk that has been unloaded since
+        assert!(grid.rewind(&delta).is_empty());
+        assert_eq!(grid.snapshot(), saved);
+    }
+
+    #[test]
+    fn test_restore_chunked() {
+        let mut grid = Grid::<TestMarker>::default()
+            .with_dimensions(UVec2::new(10, 10))
+            .with_spacing(Vec2::splat(32.))
+            .with_chunk_size(Some(4));
+        grid.set_loaded_chunks([UVec2::ZERO, UVec2::new(1, 0)].into_iter().collect());
+        let a = Entity::from_raw(1);
+        let b = Entity::from_raw(2);
+        let c = Entity::from_raw(3);
+        grid.insert(a, UVec2::new(1, 1)).unwrap();
+        grid.insert(b, UVec2::new(5, 1)).unwrap();
+        let snapshot = grid.snapshot();
+        grid.set_loaded_chunks([UVec2::ZERO].into_iter().collect());
+        grid.remove(b, UVec2::new(5, 1)).unwrap();
+        grid.suspend(b, UVec2::new(1, 0));
+        grid.suspend(c, UVec2::new(2, 0));
+
+        // `b` isn't placed in its unloaded chunk, and `c` keeps waiting for its own
+        grid.restore(&snapshot);
+        assert_eq!(grid.iter().collect::<Vec<_>>(), vec![a]);
+        let suspended = |chunk| grid.iter_suspended(chunk).collect::<Vec<_>>();
+        assert_eq!(suspended(UVec2::new(1, 0)), vec![b]);
+        assert_eq!(suspended(UVec2::new(2, 0)), vec![c]);
+    }
+
+    #[test]
+    fn test_snapshot() {
+        use bevy::ecs::entity::{EntityHashMap, MapEntities, SceneEntityMapper};
+
+        let mut grid = Grid::<TestMarker>::default()
+            .with_dimensions(UVec2::new(10, 10))
+            .with_spacing(Vec2::splat(32.))
+            .with_out_of_bounds_policy(OutOfBoundsPolicy::Overflow)
+            .with_floor_mode(FloorMode::Explicit);
+        let a = Entity::from_raw(1);
+        let b = Entity::from_raw(2);
+        let c = Entity::from_raw(3);
+        grid.insert_on_floor(a, UVec2::new(3, 1), 2).unwrap();
+        grid.insert(b, UVec2::new(1, 2)).unwrap();
+        grid.insert_overflow(c);
+
+        let mut snapshot = grid.snapshot();
+        assert_eq!(snapshot.config, grid.config());
+        assert_eq!(
+            snapshot.cells,
+            [
+                (UVec2::new(3, 1), vec![(a, 2)]),
+                (UVec2::new(1, 2), vec![(b, 0)]),
+            ]
+        );
+
+        // Load into a world where the entities got other ids
+        let mut world = bevy::ecs::world::World::new();
+        let mut entity_map = EntityHashMap::default();
+        SceneEntityMapper::world_scope(&mut entity_map, &mut world, |_, mapper| {
+            snapshot.map_entities(mapper);
+        });
+        let mut restored = Grid::<TestMarker>::default();
+        restored.restore(&snapshot);
+        assert_eq!(restored.config(), grid.config());
+        assert_eq!(
+            restored
+                .get_with_floors(UVec2::new(3, 1))
+                .collect::<Vec<_>>(),
+            [(entity_map[&a], 2)]
+        );
+        assert_eq!(restored.iter().count(), 3);
+        assert!(
+            restored
+                .iter_overflow()
+                .all(|entity| entity == entity_map[&c])
+        );
+    }
+
+    #[test]
+    fn test_reflect() {
+        use bevy::reflect::{GetField, TypePath};
+
+        assert_eq!(Grid::<TestMarker>::short_type_path(), "Grid<TestMarker, 4>");
+        assert!(
+            Grid::<TestMarker, 8>::type_path()
+                .ends_with("::Grid<bevy_uniform_grid_2d::resource::grid::tests::TestMarker, 8>")
+        );
+
+        let mut grid = Grid::<TestMarker>::default()
+            .with_dimensions(UVec2::new(10, 10))
+            .with_spacing(Vec2::splat(32.));
+        assert!(!grid.is_config_stale());
+        assert_eq!(
+            grid.get_field::<UVec2>("dimensions"),
+            Some(&UVec2::new(10, 10))
+        );
+
+        // Editing through reflection leaves the cached conversions stale
+        *grid.get_field_mut::<Vec2>("spacing").unwrap() = Vec2::splat(16.);
+        assert!(grid.is_config_stale());
+        grid.sync_config();
+        assert!(!grid.is_config_stale());
+        assert_eq!(
+            grid.world_to_grid(Vec3::new(40.0, 40.0, 0.0)).unwrap(),
+            UVec2::new(2, 2)
+        );
+    }
+
+    #[test]
+    fn test_isometric() {
+        let grid = Grid::<TestMarker>::default()
+            .with_dimensions(UVec2::new(10, 10))
+            .with_spacing(Vec2::new(64., 32.))
+            .with_projection(GridProjection::Isometric);
+
+        // The diamond of cell (0, 0) spans x -32..32 and y 0..32
+        assert_eq!(grid.grid_to_world(UVec2::new(0, 0)), Vec2::new(0.0, 16.0));
+        assert_eq!(
+            grid.world_to_grid(Vec3::new(0.0, 16.0, 0.0)).unwrap(),
+            UVec2::new(0, 0)
+        );
+        assert_eq!(
+            grid.world_to_grid(Vec3::new(20.0, 16.0, 0.0)).unwrap(),
+            UVec2::new(0, 0)
+        );
+        // One step along each axis of the cell grid
+        assert_eq!(
+            grid.world_to_grid(Vec3::new(32.0, 32.0, 0.0)).unwrap(),
+            UVec2::new(1, 0)
+        );
+        assert_eq!(
+            grid.world_to_grid(Vec3::new(-32.0, 32.0, 0.0)).unwrap(),
+            UVec2::new(0, 1)
+        );
+        // Right next to the diamond, but outside the grid
+        assert!(grid.world_to_grid(Vec3::new(28.0, 2.0, 0.0)).is_err());
+
+        for cell in [UVec2::new(0, 0), UVec2::new(3, 7), UVec2::new(9, 2)] {
+            assert_eq!(
+                grid.world_to_grid(grid.grid_to_world(cell).extend(0.))
+                    .unwrap(),
+                cell
+            );
+        }
+        assert_eq!(
+            grid.cell_corners(UVec2::new(0, 0)),
+            [
+                Vec2::new(0.0, 0.0),
+                Vec2::new(32.0, 16.0),
+                Vec2::new(0.0, 32.0),
+                Vec2::new(-32.0, 16.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_cells_in_rect() {
+        let grid = Grid::<TestMarker>::default()
+            .with_dimensions(UVec2::new(10, 10))
+            .with_spacing(Vec2::splat(10.));
+
+        let cells: Vec<UVec2> = grid
+            .get_cells_in_rect(Rect::new(-5.0, -5.0, 15.0, 5.0))
+            .collect();
+        assert_eq!(cells, vec![UVec2::new(0, 0), UVec2::new(1, 0)]);
+        assert_eq!(
+            grid.get_cells_in_rect(Rect::new(-20.0, -20.0, -5.0, -5.0))
+                .count(),
+            0
+        );
+        assert_eq!(
+            grid.get_cells_in_rect(Rect::new(95.0, 95.0, 500.0, 500.0))
+                .collect::<Vec<_>>(),
+            vec![UVec2::new(9, 9)]
+        );
+
+        // Rotated grids use the bounding box of the rect in cell coordinates
+        let grid = grid.with_transform(Affine2::from_angle(std::f32::consts::FRAC_PI_4));
+        let cells: Vec<UVec2> = grid
+            .get_cells_in_rect(Rect::new(-1.0, 1.0, 1.0, 3.0))
+            .collect();
+        assert_eq!(cells, vec![UVec2::new(0, 0)]);
+    }
+
+    #[test]
+    fn test_world_to_grid_non_finite() {
+        let grid = Grid::<TestMarker>::default()
+            .with_dimensions(UVec2::new(10, 10))
+            .with_spacing(Vec2::splat(32.));
+
+        assert!(matches!(
+            grid.world_to_grid(Vec3::new(f32::NAN, 0.0, 0.0)),
+            Err(GridError::NonFinite(_))
+        ));
+        assert!(matches!(
+            grid.world_to_grid(Vec3::new(0.0, f32::INFINITY, 0.0)),
+            Err(GridError::NonFinite(_))
+        ));
+        // Only the x and y axes are used, so z doesn't matter
+        assert_eq!(
+            grid.world_to_grid(Vec3::new(0.0, 0.0, f32::NAN)).unwrap(),
+            UVec2::new(0, 0)
+        );
+    }
+
+    #[test]
+    fn test_clamp_cell() {
+        let grid = Grid::<TestMarker>::default().with_dimensions(UVec2::new(10, 5));
+
+        assert_eq!(grid.clamp_cell(IVec2::new(-3, 2)), UVec2::new(0, 2));
+        assert_eq!(grid.clamp_cell(IVec2::new(12, 7)), UVec2::new(9, 4));
+        assert_eq!(grid.clamp_cell(IVec2::new(4, 4)), UVec2::new(4, 4));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let mut grid = Grid::<TestMarker>::default()
+            .with_dimensions(UVec2::new(10, 10))
+            .with_out_of_bounds_policy(OutOfBoundsPolicy::Overflow);
+        let inside = Entity::from_raw(0);
+        let outside = Entity::from_raw(1);
+
+        grid.insert(inside, UVec2::new(1, 1)).unwrap();
+        assert!(grid.insert_overflow(outside));
+        assert!(!grid.insert_overflow(outside));
+        assert_eq!(grid.iter_overflow().collect::<Vec<_>>(), vec![outside]);
+        assert_eq!(grid.iter().count(), 2);
+
+        assert!(grid.remove_overflow(outside));
+        assert_eq!(grid.iter().collect::<Vec<_>>(), vec![inside]);
+
+        grid.insert_overflow(outside);
+        grid.reset();
+        assert_eq!(grid.iter().count(), 0);
+    }
+
+    #[test]
+    fn test_grid_to_world() {
+        let grid = Grid::<TestMarker>::default()
+            .with_dimensions(UVec2::new(10, 10))
+            .with_spacing(Vec2::splat(32.))
+            .with_anchor(Vec2::new(16.0, 16.0));
+
+        assert_eq!(grid.grid_to_world(UVec2::new(0, 0)), Vec2::new(32.0, 32.0));
+        assert_eq!(grid.grid_to_world(UVec2::new(2, 1)), Vec2::new(96.0, 64.0));
+
+        // The center of a cell should map back to the same cell
+        let cell = UVec2::new(7, 3);
+        assert_eq!(
+            grid.world_to_grid(grid.grid_to_world(cell).extend(0.))
+                .unwrap(),
+            cell
+        );
+    }
+
     #[test]
     fn test_insert_and_get() {
         let mut grid = Grid::<TestMarker>::default()
@@ -0,0 +1,111 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    ecs::{component::Component, resource::Resource},
+    math::{IVec2, Rect, URect, UVec2},
+};
+
+use crate::resource::Grid;
+
+/// Hides the entities of a `Grid<Marker, N>` resource outside the cells the
+/// cameras can see, by setting their `Visibility` to `Hidden`, and sets it back to
+/// `Inherited` once they are in view. Only entities in cells that came into or
+/// went out of view, and entities that changed cells, are touched each frame.
+///
+/// The `Visibility` of marked entities is owned by the grid while culling is on.
+/// Entities that aren't in a cell, like the overflow list of
+/// `OutOfBoundsPolicy::Overflow`, are never hidden.
+#[derive(Resource)]
+pub struct GridCulling<Marker: Component, const N: usize = 4> {
+    /// Number of extra cells around the view that count as visible, so sprites
+    /// larger than a cell don't pop in at the edges. Defaults to `1`.
+    pub margin: u32,
+    /// Cells in view on the last update, with an exclusive `max`.
+    visible: Option<URect>,
+    marker: PhantomData<Marker>,
+}
+
+impl<Marker: Component, const N: usize> Default for GridCulling<Marker, N> {
+    fn default() -> Self {
+        Self {
+            margin: 1,
+            visible: None,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<Marker: Component, const N: usize> GridCulling<Marker, N> {
+    /// Cells in view on the last update, with an exclusive `max`. `None` if every
+    /// cell counts as visible, e.g. when there is no camera.
+    #[inline]
+    pub fn visible_cells(&self) -> Option<URect> {
+        self.visible
+    }
+
+    /// Whether `cell` was in view on the last update.
+    #[inline]
+    pub fn is_cell_visible(&self, cell: UVec2) -> bool {
+        self.visible
+            .is_none_or(|visible| contains_cell(visible, cell))
+    }
+
+    /// Cells of `grid` covered by the world-space `view` on the grid's plane,
+    /// grown by `margin`.
+    pub(crate) fn cells_in_view(&self, grid: &Grid<Marker, N>, view: Rect) -> URect {
+        let cells = grid.world_rect_to_cells(view);
+        let margin = IVec2::splat(self.margin as i32);
+        let dimensions = grid.dimensions().as_ivec2();
+        let min = (cells.min.floor().as_ivec2() - margin).clamp(IVec2::ZERO, dimensions);
+        let max = (cells.max.ceil().as_ivec2() + margin).clamp(min, dimensions);
+        URect::from_corners(min.as_uvec2(), max.as_uvec2())
+    }
+
+    /// Internal setter method for the cells in view.
+    #[inline]
+    pub(crate) fn set_visible_cells(&mut self, visible: Option<URect>) {
+        self.visible = visible;
+    }
+}
+
+/// Whether `cell` is in `rect`, which has an exclusive `max`.
+#[inline]
+pub(crate) fn contains_cell(rect: URect, cell: UVec2) -> bool {
+    cell.cmpge(rect.min).all() && cell.cmplt(rect.max).all()
+}
+
+/// Cells in `a` that aren't in `b`, both with an exclusive `max`. Rows that
+/// overlap `b` skip its columns without visiting them.
+pub(crate) fn cells_difference(a: URect, b: URect) -> impl Iterator<Item = UVec2> {
+    (a.min.y..a.max.y).flat_map(move |y| {
+        let (skip_min, skip_max) = if (b.min.y..b.max.y).contains(&y) {
+            (
+                b.min.x.clamp(a.min.x, a.max.x),
+                b.max.x.clamp(a.min.x, a.max.x),
+            )
+        } else {
+            (a.max.x, a.max.x)
+        };
+        (a.min.x..skip_min)
+            .chain(skip_max.max(skip_min)..a.max.x)
+            .map(move |x| UVec2::new(x, y))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cells_difference() {
+        let a = URect::new(0, 0, 4, 3);
+        let b = URect::new(1, 1, 3, 5);
+        let cells: Vec<_> = cells_difference(a, b).collect();
+        assert_eq!(cells.len(), 12 - 4);
+        assert!(cells.iter().all(|&cell| !contains_cell(b, cell)));
+        assert!(cells.contains(&UVec2::new(3, 2)));
+
+        assert_eq!(cells_difference(a, a).count(), 0);
+        assert_eq!(cells_difference(a, URect::new(10, 10, 12, 12)).count(), 12);
+    }
+}
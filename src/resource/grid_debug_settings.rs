@@ -0,0 +1,69 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    color::{Alpha, Color, palettes::tailwind},
+    ecs::{component::Component, resource::Resource},
+    prelude::ReflectResource,
+    reflect::Reflect,
+};
+
+use crate::type_path::impl_marker_type_path;
+
+/// Runtime settings for the debug drawing of a `Grid<Marker, N>`. Changes are
+/// picked up on the next frame, so debug drawing can be toggled without a rebuild.
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource, type_path = false)]
+pub struct GridDebugSettings<Marker: Component, const N: usize = 4> {
+    /// Whether anything is drawn at all.
+    pub enabled: bool,
+    /// Whether the grid lines are drawn.
+    pub show_lines: bool,
+    /// Whether occupied cells are shaded by their entity count.
+    pub show_heatmap: bool,
+    /// Whether heatmap cells are labeled with their entity count. Requires the
+    /// `debug_labels` feature.
+    pub show_labels: bool,
+    /// Whether a line is drawn from each entity to the center of its grid cell.
+    pub show_entity_links: bool,
+    /// Whether grid lines and heatmap cells outside the view of the active
+    /// cameras are skipped. Requires the `render` feature.
+    pub cull_to_view: bool,
+    /// Minimum on-screen distance in pixels between drawn grid lines. When
+    /// zoomed out further, only every 2nd, 4th, 8th... line is drawn. Set to
+    /// `0.0` to always draw every line. Requires the `render` feature.
+    pub lod_min_spacing: f32,
+    /// Color of the grid lines.
+    pub line_color: Color,
+    /// Width of all debug lines in pixels.
+    pub line_width: f32,
+    /// Heatmap color of a cell holding a single entity.
+    pub heatmap_low: Color,
+    /// Heatmap color of a cell at or over its inline capacity `N`.
+    pub heatmap_high: Color,
+    /// Color of the lines from entities to their grid cells.
+    pub link_color: Color,
+    #[reflect(ignore)]
+    marker: PhantomData<Marker>,
+}
+
+impl_marker_type_path!(GridDebugSettings<Marker, N>);
+
+impl<Marker: Component, const N: usize> Default for GridDebugSettings<Marker, N> {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            show_lines: true,
+            show_heatmap: false,
+            show_labels: false,
+            show_entity_links: false,
+            cull_to_view: true,
+            lod_min_spacing: 4.,
+            line_color: tailwind::GRAY_300.with_alpha(0.03).into(),
+            line_width: 2.,
+            heatmap_low: tailwind::GREEN_500.with_alpha(0.35).into(),
+            heatmap_high: tailwind::RED_500.with_alpha(0.35).into(),
+            link_color: tailwind::SKY_400.with_alpha(0.5).into(),
+            marker: PhantomData,
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use std::{any::type_name, marker::PhantomData, time::Duration};
+
+use bevy::{
+    diagnostic::DiagnosticPath,
+    ecs::{component::Component, resource::Resource},
+};
+
+use crate::type_path::short_type_name;
+
+/// Diagnostic paths for a `Grid<Marker, N>`, plus the per-frame counters that
+/// `update_grid` records into them. Paths are prefixed with `grid/<Marker>/<N>`,
+/// e.g. `grid/Marker/4/entities`, so grids that only differ in `N` don't share them.
+#[derive(Resource)]
+pub struct GridDiagnostics<Marker: Component, const N: usize = 4> {
+    entities: DiagnosticPath,
+    occupied_cells: DiagnosticPath,
+    max_per_cell: DiagnosticPath,
+    avg_per_cell: DiagnosticPath,
+    spilled: DiagnosticPath,
+    inserts: DiagnosticPath,
+    updates: DiagnosticPath,
+    removes: DiagnosticPath,
+    update_time: DiagnosticPath,
+    /// Number of `GridOperation::Insert` events written this frame.
+    pub(crate) insert_count: usize,
+    /// Number of `GridOperation::Update` events written this frame.
+    pub(crate) update_count: usize,
+    /// Number of `GridOperation::Remove` events written this frame.
+    pub(crate) remove_count: usize,
+    /// Time spent in `update_grid` this frame.
+    pub(crate) elapsed: Duration,
+    marker: PhantomData<Marker>,
+}
+
+impl<Marker: Component, const N: usize> Default for GridDiagnostics<Marker, N> {
+    fn default() -> Self {
+        let prefix = format!("grid/{}/{N}", short_type_name(type_name::<Marker>()));
+        let path = |name: &str| DiagnosticPath::new(format!("{prefix}/{name}"));
+        Self {
+            entities: path("entities"),
+            occupied_cells: path("occupied_cells"),
+            max_per_cell: path("max_per_cell"),
+            avg_per_cell: path("avg_per_cell"),
+            spilled: path("spilled"),
+            inserts: path("inserts"),
+            updates: path("updates"),
+            removes: path("removes"),
+            update_time: path("update_time"),
+            insert_count: 0,
+            update_count: 0,
+            remove_count: 0,
+            elapsed: Duration::ZERO,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<Marker: Component, const N: usize> GridDiagnostics<Marker, N> {
+    /// Total number of entities in the grid.
+    #[inline]
+    pub fn entities(&self) -> &DiagnosticPath {
+        &self.entities
+    }
+
+    /// Number of cells with at least one entity.
+    #[inline]
+    pub fn occupied_cells(&self) -> &DiagnosticPath {
+        &self.occupied_cells
+    }
+
+    /// Largest number of entities in a single cell.
+    #[inline]
+    pub fn max_per_cell(&self) -> &DiagnosticPath {
+        &self.max_per_cell
+    }
+
+    /// Average number of entities per occupied cell.
+    #[inline]
+    pub fn avg_per_cell(&self) -> &DiagnosticPath {
+        &self.avg_per_cell
+    }
+
+    /// Number of dynamic entities in cells holding more of them than the inline
+    /// capacity `N`. These cells have spilled onto the heap. Static entities are
+    /// stored apart and don't count.
+    #[inline]
+    pub fn spilled(&self) -> &DiagnosticPath {
+        &self.spilled
+    }
+
+    /// Number of `GridOperation::Insert` events per frame.
+    #[inline]
+    pub fn inserts(&self) -> &DiagnosticPath {
+        &self.inserts
+    }
+
+    /// Number of `GridOperation::Update` events per frame.
+    #[inline]
+    pub fn updates(&self) -> &DiagnosticPath {
+        &self.updates
+    }
+
+    /// Number of `GridOperation::Remove` events per frame.
+    #[inline]
+    pub fn removes(&self) -> &DiagnosticPath {
+        &self.removes
+    }
+
+    /// Time spent in `update_grid` per frame, in milliseconds.
+    #[inline]
+    pub fn update_time(&self) -> &DiagnosticPath {
+        &self.update_time
+    }
+
+    /// Every diagnostic path of the grid, e.g. for `LogDiagnosticsPlugin::filtered`.
+    pub fn paths(&self) -> [&DiagnosticPath; 9] {
+        [
+            &self.entities,
+            &self.occupied_cells,
+            &self.max_per_cell,
+            &self.avg_per_cell,
+            &self.spilled,
+            &self.inserts,
+            &self.updates,
+            &self.removes,
+            &self.update_time,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Component)]
+    struct TestMarker;
+
+    #[test]
+    fn test_paths() {
+        let diagnostics = GridDiagnostics::<TestMarker>::default();
+        assert_eq!(
+            diagnostics.entities().as_str(),
+            "grid/TestMarker/4/entities"
+        );
+        assert_eq!(
+            diagnostics.update_time().as_str(),
+            "grid/TestMarker/4/update_time"
+        );
+        assert_eq!(diagnostics.paths().len(), 9);
+
+        let other = GridDiagnostics::<TestMarker, 8>::default();
+        assert_ne!(diagnostics.entities(), other.entities());
+    }
+}
@@ -0,0 +1,64 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    color::Color,
+    ecs::{component::Component, resource::Resource},
+    math::UVec2,
+};
+
+/// Cells of a `Grid<Marker, N>` to highlight in debug mode. Highlights only last
+/// for the frame they are added in, so systems should re-add them every frame.
+#[derive(Resource)]
+pub struct GridHighlights<Marker: Component, const N: usize = 4> {
+    cells: Vec<(UVec2, Color)>,
+    marker: PhantomData<Marker>,
+}
+
+impl<Marker: Component, const N: usize> Default for GridHighlights<Marker, N> {
+    fn default() -> Self {
+        Self {
+            cells: Vec::new(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<Marker: Component, const N: usize> GridHighlights<Marker, N> {
+    /// Highlight `cell` with `color` for the current frame.
+    #[inline]
+    pub fn highlight(&mut self, cell: UVec2, color: impl Into<Color>) -> &mut Self {
+        self.cells.push((cell, color.into()));
+        self
+    }
+
+    /// Highlight every cell in `cells` with `color` for the current frame.
+    #[inline]
+    pub fn highlight_all(
+        &mut self,
+        cells: impl IntoIterator<Item = UVec2>,
+        color: impl Into<Color>,
+    ) -> &mut Self {
+        let color = color.into();
+        self.cells
+            .extend(cells.into_iter().map(|cell| (cell, color)));
+        self
+    }
+
+    /// Iterator over the highlighted cells and their colors.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (UVec2, Color)> + '_ {
+        self.cells.iter().copied()
+    }
+
+    /// Whether there are no highlighted cells.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Remove all highlights.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+}
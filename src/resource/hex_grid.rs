@@ -0,0 +1,628 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    ecs::{component::Component, entity::Entity, resource::Resource},
+    math::{IVec2, IVec3, Mat2, Vec2, Vec3, Vec3Swizzles},
+    prelude::ReflectResource,
+    reflect::Reflect,
+};
+use rustc_hash::FxHashMap;
+use smallvec::SmallVec;
+
+use crate::{error::GridError, resource::GridPlane, type_path::impl_marker_type_path};
+
+/// Axial offsets of the six neighbors of a hex, counter-clockwise starting
+/// from the `+q` direction.
+pub const HEX_DIRECTIONS: [IVec2; 6] = [
+    IVec2::new(1, 0),
+    IVec2::new(1, -1),
+    IVec2::new(0, -1),
+    IVec2::new(-1, 0),
+    IVec2::new(-1, 1),
+    IVec2::new(0, 1),
+];
+
+/// Orientation of the hexes of a `HexGrid`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HexLayout {
+    /// Hexes have a corner at the top and rows run along the `x` axis.
+    #[default]
+    PointyTop,
+    /// Hexes have a flat edge at the top and columns run along the `y` axis.
+    FlatTop,
+}
+
+impl HexLayout {
+    /// Conversion from axial hex coordinates to world space for hexes with a
+    /// corner distance of one.
+    #[inline]
+    fn matrix(self) -> Mat2 {
+        let sqrt3 = 3f32.sqrt();
+        match self {
+            HexLayout::PointyTop => {
+                Mat2::from_cols(Vec2::new(sqrt3, 0.), Vec2::new(sqrt3 / 2., 1.5))
+            }
+            HexLayout::FlatTop => Mat2::from_cols(Vec2::new(1.5, sqrt3 / 2.), Vec2::new(0., sqrt3)),
+        }
+    }
+
+    /// Angle in radians of the first corner of a hex.
+    #[inline]
+    fn start_angle(self) -> f32 {
+        match self {
+            HexLayout::PointyTop => std::f32::consts::FRAC_PI_6,
+            HexLayout::FlatTop => 0.,
+        }
+    }
+}
+
+/// Convert an axial `hex` coordinate `(q, r)` to cube coordinates `(q, r, s)`,
+/// where `q + r + s == 0`.
+#[inline]
+pub fn hex_to_cube(hex: IVec2) -> IVec3 {
+    IVec3::new(hex.x, hex.y, -hex.x - hex.y)
+}
+
+/// Round fractional axial coordinates to the hex containing them.
+#[inline]
+pub fn hex_round(hex: Vec2) -> IVec2 {
+    let cube = Vec3::new(hex.x, hex.y, -hex.x - hex.y);
+    let rounded = cube.round();
+    let diff = (rounded - cube).abs();
+    let mut result = rounded.as_ivec3();
+    // Rounding each component separately can break `q + r + s == 0`, so the
+    // component that moved the most is recomputed from the other two
+    if diff.x > diff.y && diff.x > diff.z {
+        result.x = -result.y - result.z;
+    } else if diff.y > diff.z {
+        result.y = -result.x - result.z;
+    }
+    result.xy()
+}
+
+/// Number of steps between hexes `a` and `b`.
+#[inline]
+pub fn hex_distance(a: IVec2, b: IVec2) -> u32 {
+    let delta = hex_to_cube(a - b).abs();
+    delta.max_element() as u32
+}
+
+/// Iterator over the hexes exactly `radius` steps from `center`, going around
+/// counter-clockwise. A `radius` of zero yields only `center`.
+pub fn hex_ring(center: IVec2, radius: u32) -> impl Iterator<Item = IVec2> {
+    let radius = radius as i32;
+    let mut corners = [center + HEX_DIRECTIONS[4] * radius; 6];
+    for side in 1..6 {
+        corners[side] = corners[side - 1] + HEX_DIRECTIONS[side - 1] * radius;
+    }
+    let len = (6 * radius).max(1);
+    (0..len).map(move |i| {
+        if radius == 0 {
+            return center;
+        }
+        let side = (i / radius) as usize;
+        corners[side] + HEX_DIRECTIONS[side] * (i % radius)
+    })
+}
+
+/// Iterator over the hexes within `radius` steps of `center`, ring by ring
+/// starting with `center` itself.
+pub fn hex_spiral(center: IVec2, radius: u32) -> impl Iterator<Item = IVec2> {
+    (0..=radius).flat_map(move |ring| hex_ring(center, ring))
+}
+
+/// Spatial index of every entity with the `Marker` component on a hexagonal grid.
+/// Hexes are addressed with axial coordinates `(q, r)`; see `hex_to_cube` for
+/// the matching cube coordinates. The grid is hexagon-shaped, covering every
+/// hex within `radius` steps of hex `(0, 0)`.
+///
+/// The configuration can be inspected and edited through reflection. The contents
+/// of the grid are not reflected.
+#[derive(Resource, Reflect)]
+#[reflect(Resource, type_path = false)]
+pub struct HexGrid<Marker: Component, const N: usize = 4> {
+    /// Orientation of the hexes. Defaults to `HexLayout::PointyTop`.
+    layout: HexLayout,
+    /// Distance from the center of a hex to its corners in world-space units.
+    /// Use different `x` and `y` values to squash the hexes.
+    size: Vec2,
+    /// Point in world space of the center of hex `(0, 0)`. Defaults to the origin.
+    anchor: Vec2,
+    /// Number of hexes from hex `(0, 0)` to the edge of the grid.
+    radius: u32,
+    /// World axes the grid is laid out on. Defaults to `GridPlane::XY`.
+    plane: GridPlane,
+    /// Conversion from world space relative to `anchor` to fractional axial
+    /// coordinates, cached from `layout` and `size`.
+    #[reflect(ignore)]
+    world_to_hex: Mat2,
+    /// Configuration `world_to_hex` was computed from and the contents were placed
+    /// with. Differs from the current configuration after it was edited through
+    /// reflection.
+    #[reflect(ignore)]
+    synced: Option<HexGridConfig>,
+    #[reflect(ignore)]
+    data: FxHashMap<IVec2, SmallVec<[Entity; N]>>,
+    #[reflect(ignore)]
+    marker: PhantomData<Marker>,
+}
+
+impl_marker_type_path!(HexGrid<Marker, N>);
+
+/// Configuration of a `HexGrid`, to tell when it was edited through reflection.
+type HexGridConfig = (HexLayout, Vec2, Vec2, u32, GridPlane);
+
+impl<Marker: Component, const N: usize> Default for HexGrid<Marker, N> {
+    fn default() -> Self {
+        let layout = HexLayout::default();
+        Self {
+            layout,
+            size: Vec2::ONE,
+            anchor: Vec2::ZERO,
+            radius: 0,
+            plane: GridPlane::default(),
+            world_to_hex: layout.matrix().inverse(),
+            synced: Some((layout, Vec2::ONE, Vec2::ZERO, 0, GridPlane::default())),
+            data: FxHashMap::default(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<Marker: Component, const N: usize> HexGrid<Marker, N> {
+    /// Getter method for the grid's `layout`.
+    #[inline]
+    pub fn layout(&self) -> HexLayout {
+        self.layout
+    }
+
+    /// Getter method for the grid's `size`.
+    #[inline]
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    /// Getter method for the grid's `anchor`.
+    #[inline]
+    pub fn anchor(&self) -> Vec2 {
+        self.anchor
+    }
+
+    /// Getter method for the grid's `radius`.
+    #[inline]
+    pub fn radius(&self) -> u32 {
+        self.radius
+    }
+
+    /// Getter method for the grid's `plane`.
+    #[inline]
+    pub fn plane(&self) -> GridPlane {
+        self.plane
+    }
+
+    /// Builder method to set the grid's `layout`.
+    pub fn with_layout(mut self, value: HexLayout) -> Self {
+        self.layout = value;
+        self.sync_matrix();
+        self
+    }
+
+    /// Builder method to set the grid's `size`.
+    pub fn with_size(mut self, value: impl Into<Vec2>) -> Self {
+        self.size = value.into();
+        self.sync_matrix();
+        self
+    }
+
+    /// Builder method to set the grid's `anchor`.
+    pub fn with_anchor(mut self, value: impl Into<Vec2>) -> Self {
+        self.anchor = value.into();
+        self.sync_matrix();
+        self
+    }
+
+    /// Builder method to set the grid's `radius`.
+    pub fn with_radius(mut self, value: u32) -> Self {
+        self.radius = value;
+        self.sync_matrix();
+        self
+    }
+
+    /// Builder method to set the grid's `plane`.
+    pub fn with_plane(mut self, value: GridPlane) -> Self {
+        self.plane = value;
+        self.sync_matrix();
+        self
+    }
+
+    fn sync_matrix(&mut self) {
+        self.world_to_hex = (Mat2::from_diagonal(self.size) * self.layout.matrix()).inverse();
+        self.synced = Some(self.config());
+    }
+
+    #[inline]
+    fn config(&self) -> HexGridConfig {
+        (self.layout, self.size, self.anchor, self.radius, self.plane)
+    }
+
+    /// Whether the configuration was edited through reflection since the cached
+    /// conversion was last updated. See `sync_config`.
+    #[inline]
+    pub(crate) fn is_config_stale(&self) -> bool {
+        self.synced != Some(self.config())
+    }
+
+    /// Update the cached conversion after the configuration was edited through
+    /// reflection. The grid has to be reset afterwards, since its contents may
+    /// no longer match the configuration.
+    #[inline]
+    pub(crate) fn sync_config(&mut self) {
+        self.sync_matrix();
+    }
+
+    pub fn reset(&mut self) {
+        self.data = FxHashMap::default();
+    }
+
+    /// Iterator for every entity in the grid.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.data
+            .values()
+            .flat_map(|entities| entities.iter().copied())
+    }
+
+    /// Insert an `entity` into the grid at `hex` coordinate.
+    #[inline]
+    pub fn insert(&mut self, entity: Entity, hex: IVec2) -> Result<(), GridError> {
+        if !self.contains_hex(hex) {
+            return Err(GridError::OutOfBounds(hex));
+        }
+        self.data.entry(hex).or_default().push(entity);
+        Ok(())
+    }
+
+    /// Insert an `entity` into the grid at `translation` world-space coordinate.
+    #[inline]
+    pub fn insert_at_world_position(
+        &mut self,
+        entity: Entity,
+        translation: Vec3,
+    ) -> Result<IVec2, GridError> {
+        let hex = self.world_to_hex(translation)?;
+        self.data.entry(hex).or_default().push(entity);
+        Ok(hex)
+    }
+
+    #[inline]
+    pub fn get(&self, hex: IVec2) -> impl Iterator<Item = Entity> {
+        self.data
+            .get(&hex)
+            .map_or([].iter().copied(), |v| v.iter().copied())
+    }
+
+    /// Iterator for all the entities in hexes neighboring `hex`.
+    #[inline]
+    pub fn iter_neighbors(&self, hex: IVec2) -> impl Iterator<Item = Entity> + '_ {
+        self.get_hex_neighbors(hex)
+            .flat_map(move |neighbor| self.get(neighbor))
+    }
+
+    /// Iterator for all the entities in hexes neighboring and including `hex`.
+    #[inline]
+    pub fn iter_neighbors_inclusive(&self, hex: IVec2) -> impl Iterator<Item = Entity> + '_ {
+        self.iter_neighbors(hex).chain(self.get(hex))
+    }
+
+    /// Iterator for all the entities in hexes exactly `radius` steps from `hex`.
+    #[inline]
+    pub fn iter_ring(&self, hex: IVec2, radius: u32) -> impl Iterator<Item = Entity> + '_ {
+        self.get_hexes_in_ring(hex, radius)
+            .flat_map(move |hex| self.get(hex))
+    }
+
+    /// Iterator for all the entities in hexes within `radius` steps of `hex`,
+    /// including `hex` itself, ordered from the inside out.
+    #[inline]
+    pub fn iter_spiral(&self, hex: IVec2, radius: u32) -> impl Iterator<Item = Entity> + '_ {
+        self.get_hexes_in_spiral(hex, radius)
+            .flat_map(move |hex| self.get(hex))
+    }
+
+    /// Return an iterator over all valid hex coordinates exactly `radius` steps
+    /// from `hex`. See `hex_ring`.
+    #[inline]
+    pub fn get_hexes_in_ring(&self, hex: IVec2, radius: u32) -> impl Iterator<Item = IVec2> + '_ {
+        hex_ring(hex, radius).filter(|&hex| self.contains_hex(hex))
+    }
+
+    /// Return an iterator over all valid hex coordinates within `radius` steps
+    /// of `hex`. See `hex_spiral`.
+    #[inline]
+    pub fn get_hexes_in_spiral(&self, hex: IVec2, radius: u32) -> impl Iterator<Item = IVec2> + '_ {
+        hex_spiral(hex, radius).filter(|&hex| self.contains_hex(hex))
+    }
+
+    #[inline]
+    fn remove_from_grid(&mut self, entity: Entity, hex: IVec2) -> Result<(), GridError> {
+        let Some(entities) = self.data.get_mut(&hex) else {
+            return Err(GridError::HexNotFound(hex));
+        };
+        let Some(pos) = entities.iter().position(|&e| e == entity) else {
+            return Err(GridError::EntityNotFound(entity));
+        };
+        entities.swap_remove(pos);
+        if entities.is_empty() {
+            self.data.remove(&hex);
+        }
+        Ok(())
+    }
+
+    /// Remove an `entity` located at `hex` coordinate from the grid.
+    #[inline]
+    pub fn remove(&mut self, entity: Entity, hex: IVec2) -> Result<(), GridError> {
+        self.remove_from_grid(entity, hex)
+    }
+
+    /// Change the hex coordinate of an `entity` from `current_hex` to `new_hex`.
+    #[inline]
+    pub fn update(
+        &mut self,
+        entity: Entity,
+        current_hex: IVec2,
+        new_hex: IVec2,
+    ) -> Result<(), GridError> {
+        if !self.contains_hex(new_hex) {
+            return Err(GridError::OutOfBounds(new_hex));
+        }
+        self.remove_from_grid(entity, current_hex)?;
+        self.data.entry(new_hex).or_default().push(entity);
+        Ok(())
+    }
+
+    /// Return whether a `hex` coordinate is within `radius` steps of hex `(0, 0)`.
+    #[inline]
+    pub fn contains_hex(&self, hex: IVec2) -> bool {
+        hex_distance(hex, IVec2::ZERO) <= self.radius
+    }
+
+    /// Convert a `translation` in world space to fractional axial coordinates.
+    #[inline]
+    pub fn world_to_axial(&self, translation: Vec3) -> Vec2 {
+        self.world_to_hex * (self.plane.project(translation) - self.anchor)
+    }
+
+    /// Convert a `translation` in world space to the coordinate of the hex containing it.
+    #[inline]
+    pub fn world_to_hex(&self, translation: Vec3) -> Result<IVec2, GridError> {
+        let axial = self.world_to_axial(translation);
+        if !axial.is_finite() {
+            return Err(GridError::NonFinite(translation));
+        }
+        let hex = hex_round(axial);
+        if !self.contains_hex(hex) {
+            return Err(GridError::OutOfBounds(hex));
+        }
+        Ok(hex)
+    }
+
+    /// Convert a `hex` coordinate to the position of its center on the grid's `plane`.
+    #[inline]
+    pub fn hex_to_world(&self, hex: IVec2) -> Vec2 {
+        self.anchor + self.size * (self.layout.matrix() * hex.as_vec2())
+    }
+
+    /// Positions of the six corners of `hex` on the grid's `plane`, counter-clockwise.
+    pub fn hex_corners(&self, hex: IVec2) -> [Vec2; 6] {
+        let center = self.hex_to_world(hex);
+        let start = self.layout.start_angle();
+        std::array::from_fn(|i| {
+            let angle = start + i as f32 * std::f32::consts::FRAC_PI_3;
+            center + self.size * Vec2::from_angle(angle)
+        })
+    }
+
+    /// Iterator over every occupied hex and the number of entities in it.
+    #[inline]
+    pub fn iter_occupancy(&self) -> impl Iterator<Item = (IVec2, usize)> + '_ {
+        self.data
+            .iter()
+            .map(|(&hex, entities)| (hex, entities.len()))
+    }
+
+    /// Return an iterator over all valid neighboring hex coordinates.
+    #[inline]
+    pub fn get_hex_neighbors(&self, hex: IVec2) -> HexNeighborIterator {
+        HexNeighborIterator::new(hex, self.radius)
+    }
+}
+
+pub struct HexNeighborIterator {
+    hex: IVec2,
+    radius: u32,
+    direction: usize,
+}
+
+impl HexNeighborIterator {
+    #[inline]
+    pub(crate) fn new(hex: IVec2, radius: u32) -> Self {
+        Self {
+            hex,
+            radius,
+            direction: 0,
+        }
+    }
+}
+
+impl Iterator for HexNeighborIterator {
+    type Item = IVec2;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(offset) = HEX_DIRECTIONS.get(self.direction) {
+            self.direction += 1;
+            let neighbor = self.hex + *offset;
+            if hex_distance(neighbor, IVec2::ZERO) <= self.radius {
+                return Some(neighbor);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Component)]
+    struct TestMarker;
+
+    #[test]
+    fn test_hex_distance() {
+        assert_eq!(hex_distance(IVec2::ZERO, IVec2::ZERO), 0);
+        assert_eq!(hex_distance(IVec2::ZERO, IVec2::new(1, -1)), 1);
+        assert_eq!(hex_distance(IVec2::ZERO, IVec2::new(2, 1)), 3);
+        assert_eq!(hex_distance(IVec2::new(-1, 2), IVec2::new(2, -2)), 4);
+        assert_eq!(hex_to_cube(IVec2::new(2, -3)), IVec3::new(2, -3, 1));
+    }
+
+    #[test]
+    fn test_hex_ring_and_spiral() {
+        assert_eq!(hex_ring(IVec2::ZERO, 0).collect::<Vec<_>>(), [IVec2::ZERO]);
+        let ring: Vec<_> = hex_ring(IVec2::new(1, 1), 2).collect();
+        assert_eq!(ring.len(), 12);
+        assert!(
+            ring.iter()
+                .all(|&hex| hex_distance(hex, IVec2::new(1, 1)) == 2)
+        );
+        let spiral: Vec<_> = hex_spiral(IVec2::ZERO, 3).collect();
+        assert_eq!(spiral.len(), 37);
+        assert_eq!(spiral[0], IVec2::ZERO);
+        // Every hex appears once
+        let mut unique = spiral.clone();
+        unique.sort_by_key(|hex| (hex.x, hex.y));
+        unique.dedup();
+        assert_eq!(unique.len(), 37);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = HexGrid::<TestMarker>::default().with_radius(2);
+        assert_eq!(grid.get_hex_neighbors(IVec2::ZERO).count(), 6);
+        // Corner hexes of the grid only have three neighbors inside it
+        assert_eq!(grid.get_hex_neighbors(IVec2::new(2, 0)).count(), 3);
+        assert_eq!(grid.get_hexes_in_ring(IVec2::new(2, 0), 1).count(), 3);
+        assert_eq!(grid.get_hexes_in_spiral(IVec2::ZERO, 5).count(), 19);
+    }
+
+    #[test]
+    fn test_world_to_hex_pointy_top() {
+        let grid = HexGrid::<TestMarker>::default()
+            .with_size(Vec2::splat(10.))
+            .with_anchor(Vec2::new(100., 0.))
+            .with_radius(3);
+        let sqrt3 = 3f32.sqrt();
+
+        assert_eq!(grid.world_to_hex(Vec3::new(100., 0., 0.)), Ok(IVec2::ZERO));
+        assert!(
+            grid.hex_to_world(IVec2::new(1, 0))
+                .abs_diff_eq(Vec2::new(100. + 10. * sqrt3, 0.), 1e-4)
+        );
+        assert!(
+            grid.hex_to_world(IVec2::new(0, 1))
+                .abs_diff_eq(Vec2::new(100. + 5. * sqrt3, 15.), 1e-4)
+        );
+        // Just inside the top corner of hex (0, 0)
+        assert_eq!(grid.world_to_hex(Vec3::new(100., 9.5, 0.)), Ok(IVec2::ZERO));
+        for hex in hex_spiral(IVec2::ZERO, 3) {
+            let center = grid.hex_to_world(hex).extend(0.);
+            assert_eq!(grid.world_to_hex(center), Ok(hex));
+        }
+        assert_eq!(
+            grid.world_to_hex(Vec3::new(100. + 80. * sqrt3, 0., 0.)),
+            Err(GridError::OutOfBounds(IVec2::new(8, 0)))
+        );
+        assert!(matches!(
+            grid.world_to_hex(Vec3::new(f32::NAN, 0., 0.)),
+            Err(GridError::NonFinite(_))
+        ));
+    }
+
+    #[test]
+    fn test_world_to_hex_flat_top() {
+        let grid = HexGrid::<TestMarker>::default()
+            .with_layout(HexLayout::FlatTop)
+            .with_size(Vec2::splat(10.))
+            .with_radius(3);
+        let sqrt3 = 3f32.sqrt();
+
+        assert!(
+            grid.hex_to_world(IVec2::new(1, 0))
+                .abs_diff_eq(Vec2::new(15., 5. * sqrt3), 1e-4)
+        );
+        // Just inside the right corner of hex (0, 0)
+        assert_eq!(grid.world_to_hex(Vec3::new(9.5, 0., 0.)), Ok(IVec2::ZERO));
+        for hex in hex_spiral(IVec2::ZERO, 3) {
+            let center = grid.hex_to_world(hex).extend(0.);
+            assert_eq!(grid.world_to_hex(center), Ok(hex));
+        }
+        let corners = grid.hex_corners(IVec2::ZERO);
+        assert!(corners[0].abs_diff_eq(Vec2::new(10., 0.), 1e-4));
+    }
+
+    #[test]
+    fn test_insert_update_remove() {
+        let mut grid = HexGrid::<TestMarker>::default().with_radius(1);
+        let entity = Entity::from_raw(1);
+
+        assert_eq!(
+            grid.insert(entity, IVec2::new(2, 0)),
+            Err(GridError::OutOfBounds(IVec2::new(2, 0)))
+        );
+        grid.insert(entity, IVec2::new(1, -1)).unwrap();
+        assert_eq!(
+            grid.iter_neighbors(IVec2::ZERO).collect::<Vec<_>>(),
+            [entity]
+        );
+        grid.update(entity, IVec2::new(1, -1), IVec2::new(-1, 0))
+            .unwrap();
+        assert_eq!(grid.get(IVec2::new(-1, 0)).collect::<Vec<_>>(), [entity]);
+        assert_eq!(
+            grid.remove(entity, IVec2::new(1, -1)),
+            Err(GridError::HexNotFound(IVec2::new(1, -1)))
+        );
+        grid.remove(entity, IVec2::new(-1, 0)).unwrap();
+        assert_eq!(grid.iter().count(), 0);
+    }
+
+    #[test]
+    fn test_reflect() {
+        use bevy::reflect::{GetField, TypePath};
+
+        assert_eq!(
+            HexGrid::<TestMarker>::short_type_path(),
+            "HexGrid<TestMarker, 4>"
+        );
+
+        let mut grid = HexGrid::<TestMarker>::default()
+            .with_size(Vec2::splat(10.))
+            .with_radius(5);
+        assert!(!grid.is_config_stale());
+        assert_eq!(grid.get_field::<u32>("radius"), Some(&5));
+
+        // Editing through reflection leaves the cached conversion stale
+        *grid.get_field_mut::<HexLayout>("layout").unwrap() = HexLayout::FlatTop;
+        assert!(grid.is_config_stale());
+        assert_eq!(
+            grid.world_to_hex(Vec3::new(16., 12., 0.)),
+            Ok(IVec2::new(0, 1))
+        );
+        grid.sync_config();
+        assert!(!grid.is_config_stale());
+        assert_eq!(
+            grid.world_to_hex(Vec3::new(16., 12., 0.)),
+            Ok(IVec2::new(1, 0))
+        );
+    }
+}
@@ -0,0 +1,323 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    ecs::{component::Component, resource::Resource},
+    math::UVec2,
+    reflect::Reflect,
+};
+use rustc_hash::FxHashMap;
+
+use crate::resource::GridCellIterator;
+
+/// Influence values with a smaller magnitude than this are dropped from the map.
+const INFLUENCE_EPSILON: f32 = 1e-4;
+
+/// Smallest `decay` of a map. Sources are added on top of what is left every
+/// frame, so without decay influence would grow without bound.
+const MIN_DECAY: f32 = 0.01;
+
+/// How an `InfluenceSource`'s weight falls off with distance from its cell.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Reflect)]
+pub enum InfluenceFalloff {
+    /// Every cell within the radius receives the full weight.
+    Constant,
+    /// The weight falls off linearly, reaching zero one cell past the radius.
+    #[default]
+    Linear,
+    /// The weight is multiplied by the given factor for every cell of distance.
+    Exponential(f32),
+}
+
+impl InfluenceFalloff {
+    /// Scale factor for a cell `distance` cells away from a source with `radius`.
+    #[inline]
+    pub fn factor(&self, distance: u32, radius: u32) -> f32 {
+        match *self {
+            InfluenceFalloff::Constant => 1.,
+            InfluenceFalloff::Linear => 1. - distance as f32 / (radius + 1) as f32,
+            InfluenceFalloff::Exponential(base) => base.powi(distance as i32),
+        }
+    }
+}
+
+/// Per-cell influence field for each channel (e.g. team) of the `Grid<Marker, N>`
+/// resource. Every frame, `decay` is applied and the `InfluenceSource`s of the
+/// grid's entities are added on top, so the map only starts over from scratch
+/// when `decay` is `1.0`.
+///
+/// The influence of a source that stays put converges to `weight / decay` rather
+/// than `weight`. With the default `decay` of `1.0` the two are the same.
+#[derive(Resource)]
+pub struct InfluenceMap<Marker: Component, const N: usize = 4> {
+    /// How influence falls off with distance from a source.
+    falloff: InfluenceFalloff,
+    /// Fraction of the previous frame's influence that is removed each frame.
+    /// `1.0` rebuilds the map from scratch every frame. At least `MIN_DECAY`.
+    decay: f32,
+    /// Shape of the grid in cell units. Synced from the grid every update.
+    dimensions: UVec2,
+    channels: FxHashMap<u32, FxHashMap<UVec2, f32>>,
+    marker: PhantomData<Marker>,
+}
+
+impl<Marker: Component, const N: usize> Default for InfluenceMap<Marker, N> {
+    fn default() -> Self {
+        Self {
+            falloff: InfluenceFalloff::default(),
+            decay: 1.,
+            dimensions: UVec2::ONE,
+            channels: FxHashMap::default(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<Marker: Component, const N: usize> InfluenceMap<Marker, N> {
+    /// Getter method for the map's `falloff`.
+    #[inline]
+    pub fn falloff(&self) -> InfluenceFalloff {
+        self.falloff
+    }
+
+    /// Getter method for the map's `decay`.
+    #[inline]
+    pub fn decay(&self) -> f32 {
+        self.decay
+    }
+
+    /// Builder method to set the map's `falloff`.
+    pub fn with_falloff(mut self, value: InfluenceFalloff) -> Self {
+        self.falloff = value;
+        self
+    }
+
+    /// Builder method to set the map's `decay`. Clamped to
+    /// `0.01..=1.0`.
+    pub fn with_decay(mut self, value: f32) -> Self {
+        self.set_decay(value);
+        self
+    }
+
+    /// Setter method for the map's `falloff`.
+    #[inline]
+    pub fn set_falloff(&mut self, value: InfluenceFalloff) -> &mut Self {
+        self.falloff = value;
+        self
+    }
+
+    /// Setter method for the map's `decay`. Clamped to
+    /// `0.01..=1.0`.
+    #[inline]
+    pub fn set_decay(&mut self, value: f32) -> &mut Self {
+        self.decay = value.clamp(MIN_DECAY, 1.);
+        self
+    }
+
+    /// Internal setter method for the map's `dimensions`. Should only
+    /// be done when syncing with the grid.
+    #[inline]
+    pub(crate) fn set_dimensions(&mut self, value: UVec2) -> &mut Self {
+        self.dimensions = value;
+        self
+    }
+
+    /// Remove all influence from every channel.
+    pub fn reset(&mut self) {
+        self.channels = FxHashMap::default();
+    }
+
+    /// Apply one frame of `decay` to every channel, dropping negligible values.
+    pub fn apply_decay(&mut self) {
+        if self.decay >= 1. {
+            self.channels.clear();
+            return;
+        }
+        let retain = 1. - self.decay;
+        for field in self.channels.values_mut() {
+            field.retain(|_, value| {
+                *value *= retain;
+                value.abs() >= INFLUENCE_EPSILON
+            });
+        }
+        self.channels.retain(|_, field| !field.is_empty());
+    }
+
+    /// Add influence of `weight` on `channel` centered at `cell`, spreading to
+    /// all cells within `radius` according to the map's `falloff`.
+    pub fn add_source(&mut self, channel: u32, cell: UVec2, weight: f32, radius: u32) {
+        let max = self.dimensions.saturating_sub(UVec2::ONE);
+        let min = cell.saturating_sub(UVec2::splat(radius)).min(max);
+        let max = cell.saturating_add(UVec2::splat(radius)).min(max);
+        let field = self.channels.entry(channel).or_default();
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let target = UVec2::new(x, y);
+                let distance = (target.as_ivec2() - cell.as_ivec2()).abs().max_element() as u32;
+                let value = weight * self.falloff.factor(distance, radius);
+                if value.abs() >= INFLUENCE_EPSILON {
+                    *field.entry(target).or_default() += value;
+                }
+            }
+        }
+    }
+
+    /// Influence of `channel` at `cell`. Cells without influence return `0.0`.
+    #[inline]
+    pub fn get(&self, channel: u32, cell: UVec2) -> f32 {
+        self.channels
+            .get(&channel)
+            .and_then(|field| field.get(&cell))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Iterator over all channels that currently have influence somewhere.
+    #[inline]
+    pub fn channels(&self) -> impl Iterator<Item = u32> + '_ {
+        self.channels.keys().copied()
+    }
+
+    /// Iterator over every cell with influence on `channel` and its value.
+    #[inline]
+    pub fn iter(&self, channel: u32) -> impl Iterator<Item = (UVec2, f32)> + '_ {
+        self.channels
+            .get(&channel)
+            .into_iter()
+            .flat_map(|field| field.iter().map(|(&cell, &value)| (cell, value)))
+    }
+
+    /// The channel with the highest influence at `cell` and its value.
+    pub fn dominant(&self, cell: UVec2) -> Option<(u32, f32)> {
+        self.channels
+            .iter()
+            .filter_map(|(&channel, field)| field.get(&cell).map(|&value| (channel, value)))
+            .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))
+    }
+
+    /// The cell with the most influence on `channel` and its value.
+    pub fn max_cell(&self, channel: u32) -> Option<(UVec2, f32)> {
+        self.iter(channel).max_by(|a, b| {
+            a.1.total_cmp(&b.1)
+                .then(b.0.to_array().cmp(&a.0.to_array()))
+        })
+    }
+
+    /// The cell with the least influence on `channel` and its value. Only cells
+    /// that have some influence on `channel` are considered.
+    pub fn min_cell(&self, channel: u32) -> Option<(UVec2, f32)> {
+        self.iter(channel).min_by(|a, b| {
+            a.1.total_cmp(&b.1)
+                .then(a.0.to_array().cmp(&b.0.to_array()))
+        })
+    }
+
+    /// Iterator over the frontier of `channel`: cells where `channel` is dominant
+    /// that border a cell where it isn't (either another channel dominates or
+    /// nothing has influence).
+    pub fn iter_frontier(&self, channel: u32) -> impl Iterator<Item = UVec2> + '_ {
+        self.iter(channel).filter_map(move |(cell, _)| {
+            if self.dominant(cell)?.0 != channel {
+                return None;
+            }
+            let mut neighbors = GridCellIterator::new(cell, self.dimensions);
+            neighbors
+                .any(|neighbor| {
+                    self.dominant(neighbor)
+                        .is_none_or(|(dominant, _)| dominant != channel)
+                })
+                .then_some(cell)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Component)]
+    struct TestMarker;
+
+    fn map() -> InfluenceMap<TestMarker> {
+        let mut map =
+            InfluenceMap::<TestMarker>::default().with_falloff(InfluenceFalloff::Constant);
+        map.set_dimensions(UVec2::new(10, 10));
+        map
+    }
+
+    #[test]
+    fn test_falloff() {
+        assert_eq!(InfluenceFalloff::Constant.factor(3, 3), 1.);
+        assert_eq!(InfluenceFalloff::Linear.factor(0, 3), 1.);
+        assert_eq!(InfluenceFalloff::Linear.factor(2, 3), 0.5);
+        assert_eq!(InfluenceFalloff::Exponential(0.5).factor(2, 3), 0.25);
+    }
+
+    #[test]
+    fn test_add_source() {
+        let mut map = map().with_falloff(InfluenceFalloff::Linear);
+        map.add_source(0, UVec2::new(0, 0), 2., 1);
+
+        assert_eq!(map.get(0, UVec2::new(0, 0)), 2.);
+        assert_eq!(map.get(0, UVec2::new(1, 1)), 1.);
+        assert_eq!(map.get(0, UVec2::new(2, 2)), 0.);
+        assert_eq!(map.get(1, UVec2::new(0, 0)), 0.);
+        assert_eq!(map.iter(0).count(), 4);
+    }
+
+    #[test]
+    fn test_decay() {
+        let mut map = map().with_decay(0.5);
+        map.add_source(0, UVec2::new(5, 5), 1., 0);
+        map.apply_decay();
+        assert_eq!(map.get(0, UVec2::new(5, 5)), 0.5);
+
+        map.set_decay(1.);
+        map.apply_decay();
+        assert_eq!(map.get(0, UVec2::new(5, 5)), 0.);
+        assert_eq!(map.channels().count(), 0);
+    }
+
+    #[test]
+    fn test_decay_steady_state() {
+        // A source that stays put converges to `weight / decay`
+        let mut map = map().with_decay(0.25);
+        for _ in 0..100 {
+            map.apply_decay();
+            map.add_source(0, UVec2::new(5, 5), 2., 0);
+        }
+        assert!((map.get(0, UVec2::new(5, 5)) - 8.).abs() < 1e-3);
+
+        // Without decay influence would grow forever, so it is clamped
+        let map = map.with_decay(0.);
+        assert_eq!(map.decay(), MIN_DECAY);
+    }
+
+    #[test]
+    fn test_min_max_cell() {
+        let mut map = map();
+        map.add_source(0, UVec2::new(2, 2), 1., 0);
+        map.add_source(0, UVec2::new(7, 7), 3., 0);
+        map.add_source(0, UVec2::new(4, 4), -1., 0);
+
+        assert_eq!(map.max_cell(0), Some((UVec2::new(7, 7), 3.)));
+        assert_eq!(map.min_cell(0), Some((UVec2::new(4, 4), -1.)));
+        assert_eq!(map.max_cell(1), None);
+    }
+
+    #[test]
+    fn test_frontier() {
+        let mut map = map();
+        map.add_source(0, UVec2::new(2, 5), 1., 2);
+        map.add_source(1, UVec2::new(7, 5), 1., 2);
+
+        // Team 0 claims a 5x5 square on the left edge of the grid. Its frontier is
+        // the top and bottom rows plus the column bordering team 1.
+        let frontier: Vec<UVec2> = map.iter_frontier(0).collect();
+        assert_eq!(frontier.len(), 13);
+        assert!(frontier.contains(&UVec2::new(4, 5)));
+        assert!(frontier.contains(&UVec2::new(0, 3)));
+        assert!(!frontier.contains(&UVec2::new(0, 5)));
+        assert!(!frontier.contains(&UVec2::new(2, 5)));
+        assert_eq!(map.dominant(UVec2::new(8, 5)), Some((1, 1.)));
+    }
+}
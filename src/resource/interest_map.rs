@@ -0,0 +1,115 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    ecs::{
+        component::Component,
+        entity::{Entity, EntityHashMap, EntityHashSet},
+        resource::Resource,
+    },
+    math::UVec2,
+};
+
+/// Cell and radius an observer sees the grid from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct ObserverView {
+    pub(crate) cell: UVec2,
+    pub(crate) radius: u32,
+}
+
+impl ObserverView {
+    /// Whether `cell` is within the view.
+    #[inline]
+    pub(crate) fn contains(&self, cell: UVec2) -> bool {
+        self.cell.x.abs_diff(cell.x) <= self.radius && self.cell.y.abs_diff(cell.y) <= self.radius
+    }
+}
+
+/// What a single observer can see.
+#[derive(Default)]
+pub(crate) struct ObserverInterest {
+    /// `None` while the observer isn't in the grid.
+    pub(crate) view: Option<ObserverView>,
+    pub(crate) visible: EntityHashSet,
+}
+
+/// Entities each `GridObserver` can see in a `Grid<Marker, N>` resource. Kept up
+/// to date from the `GridEvent`s of the grid and the observers' own cell changes,
+/// writing an `InterestEnter` or `InterestExit` event for every change.
+#[derive(Resource)]
+pub struct InterestMap<Marker: Component, const N: usize = 4> {
+    pub(crate) observers: EntityHashMap<ObserverInterest>,
+    marker: PhantomData<Marker>,
+}
+
+impl<Marker: Component, const N: usize> Default for InterestMap<Marker, N> {
+    fn default() -> Self {
+        Self {
+            observers: EntityHashMap::default(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<Marker: Component, const N: usize> InterestMap<Marker, N> {
+    /// Iterator for the entities `observer` can see, not including itself.
+    pub fn iter_visible(&self, observer: Entity) -> impl Iterator<Item = Entity> + '_ {
+        self.observers
+            .get(&observer)
+            .into_iter()
+            .flat_map(|interest| interest.visible.iter().copied())
+    }
+
+    /// Whether `observer` can see `entity`.
+    #[inline]
+    pub fn is_visible(&self, observer: Entity, entity: Entity) -> bool {
+        self.observers
+            .get(&observer)
+            .is_some_and(|interest| interest.visible.contains(&entity))
+    }
+
+    /// Iterator for the observers that can see `entity`. Visits every observer.
+    pub fn iter_observers(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        self.observers
+            .iter()
+            .filter(move |(_, interest)| interest.visible.contains(&entity))
+            .map(|(&observer, _)| observer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Component)]
+    struct TestMarker;
+
+    #[test]
+    fn test_view_contains() {
+        let view = ObserverView {
+            cell: UVec2::new(3, 3),
+            radius: 2,
+        };
+        assert!(view.contains(UVec2::new(1, 5)));
+        assert!(view.contains(UVec2::new(3, 3)));
+        assert!(!view.contains(UVec2::new(0, 3)));
+        assert!(!view.contains(UVec2::new(3, 6)));
+    }
+
+    #[test]
+    fn test_visibility() {
+        let mut map = InterestMap::<TestMarker>::default();
+        let observer = Entity::from_raw(1);
+        let entity = Entity::from_raw(2);
+        map.observers
+            .entry(observer)
+            .or_default()
+            .visible
+            .insert(entity);
+
+        assert!(map.is_visible(observer, entity));
+        assert!(!map.is_visible(entity, observer));
+        assert_eq!(map.iter_visible(observer).collect::<Vec<_>>(), [entity]);
+        assert_eq!(map.iter_observers(entity).collect::<Vec<_>>(), [observer]);
+        assert_eq!(map.iter_visible(entity).count(), 0);
+    }
+}
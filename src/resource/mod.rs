@@ -1,3 +1,21 @@
+mod camera_views;
 mod grid;
+#[cfg(feature = "render")]
+mod grid_culling;
+mod grid_debug_settings;
+mod grid_diagnostics;
+mod grid_highlights;
+mod hex_grid;
+mod influence_map;
+mod interest_map;
 
+pub use camera_views::*;
 pub use grid::*;
+#[cfg(feature = "render")]
+pub use grid_culling::*;
+pub use grid_debug_settings::*;
+pub use grid_diagnostics::*;
+pub use grid_highlights::*;
+pub use hex_grid::*;
+pub use influence_map::*;
+pub use interest_map::*;
@@ -0,0 +1,129 @@
+use bevy::{
+    ecs::entity::{Entity, EntityMapper, MapEntities},
+    math::{Affine2, UVec2, Vec2},
+    reflect::Reflect,
+};
+
+use crate::{
+    component::GridLayers,
+    resource::{FloorMode, GridPlane, GridProjection, OutOfBoundsPolicy},
+};
+
+/// Configuration of a `Grid`, without its contents. See `Grid::config` and
+/// `Grid::with_config`.
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct GridConfig {
+    pub dimensions: UVec2,
+    pub spacing: Vec2,
+    pub anchor: Vec2,
+    pub projection: GridProjection,
+    pub plane: GridPlane,
+    pub transform: Affine2,
+    pub hysteresis: f32,
+    pub out_of_bounds_policy: OutOfBoundsPolicy,
+    pub floor_mode: FloorMode,
+    pub deterministic: bool,
+    pub chunk_size: Option<u32>,
+}
+
+impl Default for GridConfig {
+    fn default() -> Self {
+        Self {
+            dimensions: UVec2::ONE,
+            spacing: Vec2::ONE,
+            anchor: Vec2::ZERO,
+            projection: GridProjection::default(),
+            plane: GridPlane::default(),
+            transform: Affine2::IDENTITY,
+            hysteresis: 0.,
+            out_of_bounds_policy: OutOfBoundsPolicy::default(),
+            floor_mode: FloorMode::default(),
+            deterministic: false,
+            chunk_size: None,
+        }
+    }
+}
+
+/// Configuration and contents of a `Grid`, for saving and loading levels. See
+/// `Grid::snapshot` and `Grid::restore`.
+///
+/// Entities are stored as they were when the snapshot was taken. When loading
+/// into another world, remap them with `MapEntities` first, e.g. with the entity
+/// map of the `DynamicScene` the level was loaded from. The `GridCell` components
+/// of the entities are not part of the snapshot and have to be saved with them.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GridSnapshot {
+    pub config: GridConfig,
+    /// Occupied cells with their entities and the floors they are on, ordered
+    /// by row and then column.
+    pub cells: Vec<(UVec2, Vec<(Entity, i32)>)>,
+    /// Entities outside the grid when using `OutOfBoundsPolicy::Overflow`.
+    pub overflow: Vec<Entity>,
+    /// Cells with entities marked `GridStatic`, laid out like `cells`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub statics: Vec<(UVec2, Vec<(Entity, i32)>)>,
+    /// Layers of the entities not on `GridLayers::DEFAULT`, ordered by `Entity`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub layers: Vec<(Entity, GridLayers)>,
+}
+
+impl MapEntities for GridSnapshot {
+    fn map_entities<E: EntityMapper>(&mut self, entity_mapper: &mut E) {
+        for (_, entities) in self.cells.iter_mut().chain(&mut self.statics) {
+            for (entity, _) in entities {
+                *entity = entity_mapper.get_mapped(*entity);
+            }
+        }
+        self.overflow.map_entities(entity_mapper);
+        for (entity, _) in &mut self.layers {
+            *entity = entity_mapper.get_mapped(*entity);
+        }
+    }
+}
+
+/// Contents of the cells of a `Grid` that changed between two checkpoints, as they
+/// were at the earlier one. Used to roll the grid back without a full snapshot;
+/// see `Grid::checkpoint` and `Grid::rewind`. The configuration is not included.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GridDelta {
+    /// Changed cells with their entities and the floors they were on, ordered
+    /// by row and then column. Cells that were empty have no entities.
+    pub cells: Vec<(UVec2, Vec<(Entity, i32)>)>,
+    /// Entities that entered or left the overflow list, and whether they were in it.
+    pub overflow: Vec<(Entity, bool)>,
+    /// Layers of the entities in `cells` not on `GridLayers::DEFAULT`, ordered
+    /// by `Entity`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub layers: Vec<(Entity, GridLayers)>,
+}
+
+impl GridDelta {
+    /// Whether nothing changed between the checkpoints.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty() && self.overflow.is_empty()
+    }
+}
+
+impl MapEntities for GridDelta {
+    fn map_entities<E: EntityMapper>(&mut self, entity_mapper: &mut E) {
+        for (_, entities) in &mut self.cells {
+            for (entity, _) in entities {
+                *entity = entity_mapper.get_mapped(*entity);
+            }
+        }
+        for (entity, _) in &mut self.overflow {
+            *entity = entity_mapper.get_mapped(*entity);
+        }
+        for (entity, _) in &mut self.layers {
+            *entity = entity_mapper.get_mapped(*entity);
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use bevy::{
+    color::{Color, Mix},
+    ecs::component::Component,
+    gizmos::{config::GizmoConfigGroup, gizmos::Gizmos},
+    math::{UVec2, Vec2},
+};
+
+use crate::resource::Grid;
+
+/// Number of horizontal lines used to shade a filled cell.
+const FILL_LINES: u32 = 8;
+
+/// Draw `cell` of `grid` as an outlined cell shaded with evenly spaced lines
+/// along the cell `x` axis, on the grid's `plane`.
+pub(crate) fn draw_filled_cell<Config: GizmoConfigGroup, Marker: Component, const N: usize>(
+    gizmos: &mut Gizmos<Config>,
+    grid: &Grid<Marker, N>,
+    cell: UVec2,
+    color: Color,
+) {
+    let min = cell.as_vec2();
+    let max = min + Vec2::ONE;
+    let mut line = |start: Vec2, end: Vec2| {
+        gizmos.line(
+            grid.cell_to_world_3d(start),
+            grid.cell_to_world_3d(end),
+            color,
+        );
+    };
+    for i in 0..=FILL_LINES {
+        let y = min.y + i as f32 / FILL_LINES as f32;
+        line(Vec2::new(min.x, y), Vec2::new(max.x, y));
+    }
+    line(min, Vec2::new(min.x, max.y));
+    line(Vec2::new(max.x, min.y), max);
+}
+
+/// Heatmap color for a cell holding `count` entities. The gradient runs from
+/// `low` for a single entity to `high` once the cell reaches its inline capacity
+/// `capacity`, so cells that spill onto the heap always read as hot.
+pub(crate) fn heatmap_color(count: usize, capacity: usize, low: Color, high: Color) -> Color {
+    let t = if capacity <= 1 {
+        1.
+    } else {
+        (count.saturating_sub(1) as f32 / (capacity - 1) as f32).clamp(0., 1.)
+    };
+    Color::Oklaba(low.into()).mix(&high, t)
+}
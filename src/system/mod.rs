@@ -1,5 +1,40 @@
+mod debug_draw;
+mod sync_debug_gizmo_config;
+#[cfg(feature = "render")]
+mod update_camera_views;
+mod update_debug_entity_links;
 mod update_debug_grid_lines;
+mod update_debug_heatmap;
+#[cfg(feature = "debug_labels")]
+mod update_debug_heatmap_labels;
+mod update_debug_hex_grid;
+mod update_debug_highlights;
 mod update_grid;
+mod update_grid_chunks;
+mod update_grid_components;
+#[cfg(feature = "render")]
+mod update_grid_culling;
+mod update_grid_diagnostics;
+mod update_hex_grid;
+mod update_influence_map;
+mod update_interest_map;
 
+pub(crate) use sync_debug_gizmo_config::*;
+#[cfg(feature = "render")]
+pub(crate) use update_camera_views::*;
+pub(crate) use update_debug_entity_links::*;
 pub(crate) use update_debug_grid_lines::*;
+pub(crate) use update_debug_heatmap::*;
+#[cfg(feature = "debug_labels")]
+pub(crate) use update_debug_heatmap_labels::*;
+pub(crate) use update_debug_hex_grid::*;
+pub(crate) use update_debug_highlights::*;
 pub(crate) use update_grid::*;
+pub(crate) use update_grid_chunks::*;
+pub(crate) use update_grid_components::*;
+#[cfg(feature = "render")]
+pub(crate) use update_grid_culling::*;
+pub(crate) use update_grid_diagnostics::*;
+pub(crate) use update_hex_grid::*;
+pub(crate) use update_influence_map::*;
+pub(crate) use update_interest_map::*;
@@ -0,0 +1,22 @@
+use bevy::{
+    ecs::{
+        change_detection::DetectChanges,
+        component::Component,
+        system::{Res, ResMut},
+    },
+    gizmos::config::GizmoConfigStore,
+};
+
+use crate::{gizmos::GridGizmos, resource::GridDebugSettings};
+
+pub(crate) fn sync_debug_gizmo_config<Marker: Component, const N: usize>(
+    settings: Res<GridDebugSettings<Marker, N>>,
+    mut config_store: ResMut<GizmoConfigStore>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    let (config, _) = config_store.config_mut::<GridGizmos<Marker>>();
+    config.enabled = settings.enabled;
+    config.line.width = settings.line_width;
+}
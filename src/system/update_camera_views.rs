@@ -0,0 +1,37 @@
+use bevy::{
+    ecs::system::{Query, ResMut},
+    math::Vec2,
+    render::camera::Camera,
+    transform::components::GlobalTransform,
+};
+
+use crate::resource::{CameraView, CameraViews};
+
+pub(crate) fn update_camera_views(
+    mut camera_views: ResMut<CameraViews>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+) {
+    camera_views.views.clear();
+    for (camera, transform) in &cameras {
+        if !camera.is_active {
+            continue;
+        }
+        let Some(size) = camera.logical_viewport_size() else {
+            continue;
+        };
+        let corners = [
+            Vec2::ZERO,
+            Vec2::new(size.x, 0.),
+            Vec2::new(0., size.y),
+            size,
+        ]
+        .map(|corner| camera.viewport_to_world(transform, corner));
+        let [Ok(a), Ok(b), Ok(c), Ok(d)] = corners else {
+            continue;
+        };
+        camera_views.views.push(CameraView {
+            corners: [a, b, c, d],
+            viewport_size: size,
+        });
+    }
+}
@@ -0,0 +1,37 @@
+use bevy::{
+    ecs::{
+        component::Component,
+        system::{Query, Res},
+    },
+    gizmos::gizmos::Gizmos,
+};
+
+use crate::{
+    component::GridCell,
+    gizmos::GridGizmos,
+    position::GridPosition,
+    resource::{Grid, GridDebugSettings},
+};
+
+pub(crate) fn update_debug_entity_links<Marker: Component, const N: usize, P: GridPosition>(
+    mut gizmos: Gizmos<GridGizmos<Marker>>,
+    grid: Res<Grid<Marker, N>>,
+    settings: Res<GridDebugSettings<Marker, N>>,
+    entities: Query<(&P, &GridCell<Marker, N>)>,
+) {
+    if !settings.enabled || !settings.show_entity_links {
+        return;
+    }
+    let plane = grid.plane();
+    // Members of `Grid<Marker, N>` components are laid out in another grid
+    for (position, cell) in entities.iter().filter(|(_, cell)| cell.grid().is_none()) {
+        let position = position.grid_position();
+        // Link to the cell center at the entity's depth, so the line stays on screen
+        let depth = position.dot(plane.normal());
+        gizmos.line(
+            position,
+            plane.unproject(grid.grid_to_world(cell.inner), depth),
+            settings.link_color,
+        );
+    }
+}
@@ -1,29 +1,95 @@
 use bevy::{
-    color::{Alpha, palettes::tailwind},
     ecs::{component::Component, system::Res},
     gizmos::gizmos::Gizmos,
-    math::Vec2,
+    math::{Rect, Vec2},
 };
 
-use crate::resource::Grid;
+use crate::{
+    gizmos::GridGizmos,
+    resource::{CameraViews, Grid, GridDebugSettings},
+};
 
 pub(crate) fn update_debug_grid_lines<Marker: Component, const N: usize>(
-    mut gizmos: Gizmos,
+    mut gizmos: Gizmos<GridGizmos<Marker>>,
     grid: Res<Grid<Marker, N>>,
+    settings: Res<GridDebugSettings<Marker, N>>,
+    camera_views: Option<Res<CameraViews>>,
 ) {
-    let min = grid.anchor();
-    let max = grid.dimensions().as_vec2() * grid.spacing() + min;
+    if !settings.enabled || !settings.show_lines {
+        return;
+    }
+    let dimensions = grid.dimensions();
+    let bounds = Rect::from_corners(Vec2::ZERO, dimensions.as_vec2());
+
+    // Clip the lines to what the cameras can see and thin them out when zoomed out.
+    // Clipping happens in cell coordinates, so rotated and isometric grids use the
+    // bounding box of the view.
+    let visible = camera_views
+        .filter(|_| settings.cull_to_view)
+        .and_then(|camera_views| camera_views.visible_rect(grid.plane()));
+    let (clip, step) = match visible {
+        Some((view, world_per_pixel)) => {
+            let clip = bounds.intersect(grid.world_rect_to_cells(view));
+            if clip.is_empty() {
+                return;
+            }
+            let origin = grid.cell_to_world(Vec2::ZERO);
+            let cell_size = Vec2::new(
+                grid.cell_to_world(Vec2::X).distance(origin),
+                grid.cell_to_world(Vec2::Y).distance(origin),
+            );
+            (
+                clip,
+                lod_step(cell_size / world_per_pixel, settings.lod_min_spacing),
+            )
+        }
+        None => (bounds, 1),
+    };
+    let min = clip.min.ceil().as_uvec2();
+    let max = clip.max.floor().as_uvec2().min(dimensions);
+
+    let mut line = |start: Vec2, end: Vec2| {
+        gizmos.line(
+            grid.cell_to_world_3d(start),
+            grid.cell_to_world_3d(end),
+            settings.line_color,
+        );
+    };
+    for x in (min.x..=max.x).filter(|x| x % step == 0 || *x == dimensions.x) {
+        let x = x as f32;
+        line(Vec2::new(x, clip.min.y), Vec2::new(x, clip.max.y));
+    }
+    for y in (min.y..=max.y).filter(|y| y % step == 0 || *y == dimensions.y) {
+        let y = y as f32;
+        line(Vec2::new(clip.min.x, y), Vec2::new(clip.max.x, y));
+    }
+}
 
-    for x in 0..=grid.dimensions().x {
-        let x = x as f32 * grid.spacing().x + min.x;
-        let start = Vec2::new(x, min.y);
-        let end = Vec2::new(x, max.y);
-        gizmos.line_2d(start, end, tailwind::GRAY_300.with_alpha(0.03));
+/// Draw only every `step`-th line so lines are at least `min_spacing` pixels
+/// apart, given the on-screen size of a cell in pixels. Always a power of two
+/// so lines don't jump around while zooming.
+fn lod_step(cell_pixels: Vec2, min_spacing: f32) -> u32 {
+    let cell_pixels = cell_pixels.min_element();
+    if min_spacing <= 0. || cell_pixels >= min_spacing || cell_pixels <= 0. {
+        return 1;
     }
-    for y in 0..=grid.dimensions().y {
-        let y = y as f32 * grid.spacing().y + min.y;
-        let start = Vec2::new(min.x, y);
-        let end = Vec2::new(max.x, y);
-        gizmos.line_2d(start, end, tailwind::GRAY_300.with_alpha(0.03));
+    ((min_spacing / cell_pixels).ceil() as u32).next_power_of_two()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lod_step() {
+        // Cells that are far enough apart draw every line
+        assert_eq!(lod_step(Vec2::new(20., 30.), 8.), 1);
+        assert_eq!(lod_step(Vec2::splat(8.), 8.), 1);
+        assert_eq!(lod_step(Vec2::splat(4.), 0.), 1);
+        // Steps go up in powers of two as the cells get smaller
+        assert_eq!(lod_step(Vec2::splat(7.), 8.), 2);
+        assert_eq!(lod_step(Vec2::splat(4.), 8.), 2);
+        assert_eq!(lod_step(Vec2::splat(3.), 8.), 4);
+        assert_eq!(lod_step(Vec2::new(100., 0.5), 8.), 16);
     }
 }
@@ -0,0 +1,41 @@
+use bevy::{
+    ecs::{component::Component, system::Res},
+    gizmos::gizmos::Gizmos,
+    math::Rect,
+};
+
+use crate::{
+    gizmos::GridGizmos,
+    resource::{CameraViews, Grid, GridDebugSettings},
+    system::debug_draw::{draw_filled_cell, heatmap_color},
+};
+
+pub(crate) fn update_debug_heatmap<Marker: Component, const N: usize>(
+    mut gizmos: Gizmos<GridGizmos<Marker>>,
+    grid: Res<Grid<Marker, N>>,
+    settings: Res<GridDebugSettings<Marker, N>>,
+    camera_views: Option<Res<CameraViews>>,
+) {
+    if !settings.enabled || !settings.show_heatmap {
+        return;
+    }
+    let view = camera_views
+        .filter(|_| settings.cull_to_view)
+        .and_then(|camera_views| camera_views.visible_rect(grid.plane()))
+        .map(|(view, _)| view);
+    for (cell, count) in grid.iter_dynamic_occupancy() {
+        if let Some(view) = view {
+            let [first, corners @ ..] = grid.cell_corners(cell);
+            let bounds = corners
+                .into_iter()
+                .fold(Rect::from_corners(first, first), |bounds, corner| {
+                    bounds.union_point(corner)
+                });
+            if bounds.intersect(view).is_empty() {
+                continue;
+            }
+        }
+        let color = heatmap_color(count, N, settings.heatmap_low, settings.heatmap_high);
+        draw_filled_cell(&mut gizmos, &grid, cell, color);
+    }
+}
@@ -0,0 +1,81 @@
+use bevy::{
+    color::{Color, palettes::tailwind},
+    ecs::{
+        change_detection::DetectChanges,
+        component::Component,
+        entity::Entity,
+        system::{Commands, Local, Query, Res},
+    },
+    math::UVec2,
+    text::{Text2d, TextColor, TextFont},
+    transform::components::Transform,
+};
+use rustc_hash::FxHashMap;
+
+use crate::{
+    resource::{EntityKind, Grid, GridDebugSettings},
+    snapshot::GridConfig,
+};
+
+/// Offset of the labels along the grid's normal so they render above the heatmap.
+const LABEL_Z: f32 = 1.;
+
+pub(crate) fn update_debug_heatmap_labels<Marker: Component, const N: usize>(
+    mut commands: Commands,
+    grid: Res<Grid<Marker, N>>,
+    settings: Res<GridDebugSettings<Marker, N>>,
+    mut labels: Local<FxHashMap<UVec2, Entity>>,
+    mut config: Local<Option<GridConfig>>,
+    mut texts: Query<(&mut Text2d, &mut TextFont, &mut Transform)>,
+) {
+    if !settings.enabled || !settings.show_heatmap || !settings.show_labels {
+        for (_, label) in labels.drain() {
+            commands.entity(label).despawn();
+        }
+        return;
+    }
+    if !grid.is_changed() && !settings.is_changed() {
+        return;
+    }
+    // Despawn labels of cells without dynamic entities
+    labels.retain(|&cell, &mut label| {
+        if grid.get_of_kind(cell, EntityKind::Dynamic).next().is_some() {
+            return true;
+        }
+        commands.entity(label).despawn();
+        false
+    });
+    // Existing labels follow their cells when the grid is moved or reshaped
+    let current = grid.config();
+    let reshaped = config.replace(current) != Some(current);
+    let font_size = grid.spacing().min_element() * 0.5;
+    for (cell, count) in grid.iter_dynamic_occupancy() {
+        let text = count.to_string();
+        let transform =
+            Transform::from_translation(grid.plane().unproject(grid.grid_to_world(cell), LABEL_Z));
+        if let Some(&label) = labels.get(&cell) {
+            if let Ok((mut label, mut font, mut label_transform)) = texts.get_mut(label) {
+                if label.0 != text {
+                    label.0 = text;
+                }
+                if reshaped {
+                    font.font_size = font_size;
+                    *label_transform = transform;
+                }
+            }
+            continue;
+        }
+        let label = commands
+            .spawn((
+                Text2d::new(text),
+                TextFont {
+                    font_size,
+                    ..Default::default()
+                },
+                TextColor(Color::from(tailwind::GRAY_100)),
+                transform,
+            ))
+            .id();
+        labels.insert(cell, label);
+    }
+}
@@ -0,0 +1,31 @@
+use bevy::{
+    color::{Alpha, palettes::tailwind},
+    ecs::{component::Component, system::Res},
+    gizmos::gizmos::Gizmos,
+    math::IVec2,
+};
+
+use crate::{gizmos::HexGridGizmos, resource::HexGrid};
+
+pub(crate) fn update_debug_hex_grid<Marker: Component, const N: usize>(
+    mut gizmos: Gizmos<HexGridGizmos<Marker>>,
+    grid: Res<HexGrid<Marker, N>>,
+) {
+    let line_color = tailwind::GRAY_300.with_alpha(0.1);
+    let occupied_color = tailwind::GREEN_500.with_alpha(0.5);
+    for hex in grid.get_hexes_in_spiral(IVec2::ZERO, grid.radius()) {
+        let color = if grid.get(hex).next().is_some() {
+            occupied_color
+        } else {
+            line_color
+        };
+        let corners = grid.hex_corners(hex);
+        gizmos.linestrip(
+            corners
+                .into_iter()
+                .chain([corners[0]])
+                .map(|corner| grid.plane().unproject(corner, 0.)),
+            color,
+        );
+    }
+}
@@ -0,0 +1,37 @@
+use bevy::{
+    ecs::{
+        component::Component,
+        system::{Res, ResMut},
+    },
+    gizmos::gizmos::Gizmos,
+};
+
+use crate::{
+    gizmos::GridGizmos,
+    resource::{Grid, GridDebugSettings, GridHighlights},
+    system::debug_draw::draw_filled_cell,
+};
+
+pub(crate) fn update_debug_highlights<Marker: Component, const N: usize>(
+    mut gizmos: Gizmos<GridGizmos<Marker>>,
+    grid: Res<Grid<Marker, N>>,
+    settings: Res<GridDebugSettings<Marker, N>>,
+    highlights: Res<GridHighlights<Marker, N>>,
+) {
+    if !settings.enabled {
+        return;
+    }
+    for (cell, color) in highlights.iter() {
+        if grid.contains_cell(cell) {
+            draw_filled_cell(&mut gizmos, &grid, cell, color);
+        }
+    }
+}
+
+pub(crate) fn clear_grid_highlights<Marker: Component, const N: usize>(
+    mut highlights: ResMut<GridHighlights<Marker, N>>,
+) {
+    if !highlights.is_empty() {
+        highlights.clear();
+    }
+}
@@ -1,90 +1,508 @@
 use bevy::{
     ecs::{
+        change_detection::DetectChanges,
         component::Component,
-        entity::Entity,
+        entity::{Entity, EntityHashSet},
         event::{EventReader, EventWriter},
-        query::{Changed, With},
-        system::{Commands, Query, QueryLens, ResMut},
+        query::{Added, With, Without},
+        removal_detection::RemovedComponents,
+        system::{Commands, Query, ResMut},
+        world::{Mut, Ref},
     },
-    transform::components::Transform,
+    math::{UVec2, Vec3},
+    platform::time::Instant,
 };
 
 use crate::{
-    component::GridCell,
+    component::{GridCell, GridFloor, GridLayers, GridStatic, InGrid},
     error::GridError,
     event::{GridEvent, GridOperation, TransformGridEvent},
-    resource::Grid,
+    position::GridPosition,
+    resource::{FloorMode, Grid, GridDiagnostics, OutOfBoundsPolicy},
 };
 
-pub(crate) fn update_grid<Marker: Component, const N: usize>(
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn update_grid<Marker: Component, const N: usize, P: GridPosition>(
     mut commands: Commands,
     mut grid: ResMut<Grid<Marker, N>>,
-    mut transforms: Query<&Transform, With<Marker>>,
-    mut changed_transforms: Query<&Transform, (Changed<Transform>, With<Marker>)>,
-    mut grid_elements: Query<(Entity, Option<&mut GridCell<Marker, N>>), With<Marker>>,
+    mut grid_elements: Query<
+        (
+            Entity,
+            Ref<P>,
+            Option<Ref<GridFloor>>,
+            Option<Ref<GridLayers>>,
+            Option<&mut GridCell<Marker, N>>,
+        ),
+        (With<Marker>, Without<InGrid>, Without<GridStatic>),
+    >,
+    statics: Query<
+        (
+            Entity,
+            &P,
+            Option<&GridFloor>,
+            Option<&GridLayers>,
+            Option<&GridCell<Marker, N>>,
+        ),
+        (With<Marker>, Without<InGrid>, With<GridStatic>),
+    >,
+    added_statics: Query<Entity, (With<Marker>, Without<InGrid>, Added<GridStatic>)>,
+    mut removed_statics: RemovedComponents<GridStatic>,
+    mut removed_members: RemovedComponents<InGrid>,
     mut grid_events: EventWriter<GridEvent>,
     mut transform_grid_events: EventReader<TransformGridEvent<Marker, N>>,
+    mut diagnostics: Option<ResMut<GridDiagnostics<Marker, N>>>,
 ) {
-    let mut grid_elements: QueryLens<
-        (Entity, &Transform, Option<&mut GridCell<Marker, N>>),
-        With<Marker>,
-    > = if transform_grid_events.is_empty() {
-        changed_transforms.join_filtered(&mut grid_elements)
-    } else {
-        for event in transform_grid_events.read() {
-            if let Some(dimensions) = event.dimensions {
-                grid.set_dimensions(dimensions);
-            };
-            if let Some(spacing) = event.spacing {
-                grid.set_spacing(spacing);
-            };
-            if let Some(anchor) = event.anchor {
-                grid.set_anchor(anchor);
-            };
-        }
+    let start = diagnostics.is_some().then(Instant::now);
+    // The configuration was edited through reflection, e.g. in an inspector
+    let mut reset = grid.is_config_stale();
+    if reset {
+        grid.sync_config();
+    }
+    // Entities in chunks that loaded or unloaded need to be re-evaluated
+    let (deactivated_chunks, activated_chunks) = grid.take_chunk_changes();
+    // Events with a `grid` target component grids, see `update_grid_components`
+    for event in transform_grid_events
+        .read()
+        .filter(|event| event.grid.is_none())
+    {
+        if let Some(dimensions) = event.dimensions {
+            grid.set_dimensions(dimensions);
+        };
+        if let Some(spacing) = event.spacing {
+            grid.set_spacing(spacing);
+        };
+        if let Some(anchor) = event.anchor {
+            grid.set_anchor(anchor);
+        };
+        if let Some(transform) = event.transform {
+            grid.set_transform(transform);
+        };
+        reset = true;
+    }
+    if reset {
         grid.reset();
-        transforms.join_filtered(&mut grid_elements)
+    }
+    let deterministic = grid.deterministic();
+    let mut updater =
+        GridUpdater::new(&mut commands, &mut grid_events).with_sorted_events(deterministic);
+    // Entities taken out of unloaded chunks, whose `GridCell` is now stale, and
+    // suspended entities placed again in loaded chunks. A reset re-evaluates
+    // every entity anyway.
+    let mut unloaded = EntityHashSet::default();
+    let mut reloaded = EntityHashSet::default();
+    if !reset {
+        for chunk in deactivated_chunks {
+            for (entity, cell) in grid.suspend_chunk(chunk) {
+                updater.remove::<Marker, N>(None, entity, cell);
+                unloaded.insert(entity);
+            }
+        }
+        let mut reloaded_statics = Vec::new();
+        for chunk in activated_chunks {
+            for entity in grid.take_suspended(chunk) {
+                if let Ok((_, position, floor, layers, current_cell)) =
+                    grid_elements.get_mut(entity)
+                {
+                    updater.update(
+                        &mut grid,
+                        None,
+                        entity,
+                        position.grid_position(),
+                        floor.map(|floor| floor.0),
+                        layers.map(|layers| *layers).unwrap_or_default(),
+                        current_cell.filter(|_| !unloaded.contains(&entity)),
+                        false,
+                    );
+                } else if let Ok((_, position, floor, layers, current_cell)) = statics.get(entity) {
+                    reloaded_statics.extend(updater.update_static(
+                        &mut grid,
+                        entity,
+                        position.grid_position(),
+                        floor.map(|floor| floor.0),
+                        layers.copied().unwrap_or_default(),
+                        current_cell.filter(|_| !unloaded.contains(&entity)),
+                    ));
+                } else {
+                    continue;
+                }
+                reloaded.insert(entity);
+            }
+        }
+        grid.extend_static(reloaded_statics);
+    }
+    // Entities that stopped being static move to the dynamic part of the grid in
+    // place. After a reset they are inserted again below.
+    for entity in removed_statics.read() {
+        let Ok((_, _, _, _, Some(cell))) = grid_elements.get(entity) else {
+            continue;
+        };
+        if !reset && grid.remove_static(entity, cell.inner).is_ok() {
+            let _ = grid.insert_with_layers(entity, cell.inner, cell.floor(), cell.layers());
+        }
+    }
+    // Static entities are only indexed when they are added, or again after a reset
+    let indexed: Vec<Entity> = match reset {
+        true => statics.iter().map(|(entity, ..)| entity).collect(),
+        false => added_statics.iter().collect(),
     };
-    for (entity, transform, current_cell) in grid_elements.query() {
-        match grid.world_to_grid(transform.translation) {
+    let mut new_statics = Vec::new();
+    for (entity, position, floor, layers, current_cell) in statics.iter_many(indexed) {
+        if reloaded.contains(&entity) {
+            continue;
+        }
+        let current_cell = current_cell.filter(|_| !unloaded.contains(&entity));
+        // Entities that were dynamic until now leave the dynamic part of the grid
+        if let Some(current_cell) = current_cell.filter(|_| !reset) {
+            let _ = grid.remove(entity, current_cell.inner);
+        }
+        grid.remove_overflow(entity);
+        new_statics.extend(updater.update_static(
+            &mut grid,
+            entity,
+            position.grid_position(),
+            floor.map(|floor| floor.0),
+            layers.copied().unwrap_or_default(),
+            current_cell,
+        ));
+    }
+    grid.extend_static(new_statics);
+    // Entities that left a component grid join the resource grid right away
+    let former_members: EntityHashSet = removed_members.read().collect();
+    for (entity, position, floor, layers, current_cell) in &mut grid_elements {
+        // After a reset every entity needs to be re-inserted
+        if !reset
+            && !former_members.contains(&entity)
+            && !position.is_changed()
+            && !floor.as_ref().is_some_and(DetectChanges::is_changed)
+            && !layers.as_ref().is_some_and(DetectChanges::is_changed)
+        {
+            continue;
+        }
+        // Suspended entities in chunks that loaded were placed above
+        if reloaded.contains(&entity) {
+            continue;
+        }
+        updater.update(
+            &mut grid,
+            None,
+            entity,
+            position.grid_position(),
+            floor.map(|floor| floor.0),
+            layers.map(|layers| *layers).unwrap_or_default(),
+            current_cell.filter(|_| !unloaded.contains(&entity)),
+            reset,
+        );
+    }
+    let (inserts, updates, removes) = updater.flush();
+    if let Some(diagnostics) = diagnostics.as_deref_mut() {
+        diagnostics.insert_count = inserts;
+        diagnostics.update_count = updates;
+        diagnostics.remove_count = removes;
+        diagnostics.elapsed = start.map(|start| start.elapsed()).unwrap_or_default();
+    }
+}
+
+/// Moves entities between the cells of a grid, keeping their `GridCell` in sync
+/// and writing a `GridEvent` for every change. Shared by resource and component grids.
+/// Call `flush` when done, which writes any buffered events.
+pub(crate) struct GridUpdater<'a, 'w, 's, 'e> {
+    commands: &'a mut Commands<'w, 's>,
+    grid_events: &'a mut EventWriter<'e, GridEvent>,
+    /// Events held back until `flush` to be written in `Entity` order.
+    sorted_events: Option<Vec<GridEvent>>,
+    /// Number of `GridOperation::Insert` events written.
+    inserts: usize,
+    /// Number of `GridOperation::Update` events written.
+    updates: usize,
+    /// Number of `GridOperation::Remove` events written.
+    removes: usize,
+}
+
+impl<'a, 'w, 's, 'e> GridUpdater<'a, 'w, 's, 'e> {
+    pub(crate) fn new(
+        commands: &'a mut Commands<'w, 's>,
+        grid_events: &'a mut EventWriter<'e, GridEvent>,
+    ) -> Self {
+        Self {
+            commands,
+            grid_events,
+            sorted_events: None,
+            inserts: 0,
+            updates: 0,
+            removes: 0,
+        }
+    }
+
+    /// Builder method to buffer events and write them in `Entity` order on `flush`,
+    /// instead of in query order.
+    pub(crate) fn with_sorted_events(mut self, sorted: bool) -> Self {
+        self.sorted_events = sorted.then(Vec::new);
+        self
+    }
+
+    /// Write the buffered events, if any, and return the number of inserts,
+    /// updates and removes.
+    pub(crate) fn flush(self) -> (usize, usize, usize) {
+        if let Some(mut events) = self.sorted_events {
+            // Stable, so the events of a single entity keep their order
+            events.sort_by_key(|event| event.entity);
+            self.grid_events.write_batch(events);
+        }
+        (self.inserts, self.updates, self.removes)
+    }
+
+    #[inline]
+    fn write(&mut self, event: GridEvent) {
+        match &mut self.sorted_events {
+            Some(events) => events.push(event),
+            None => {
+                self.grid_events.write(event);
+            }
+        }
+    }
+
+    /// Move `entity` to the cell of `grid` at `position`. `grid_entity` is the entity
+    /// the grid is a component of, if any, which `position` is relative to.
+    /// `explicit_floor` is the entity's `GridFloor`, used with `FloorMode::Explicit`,
+    /// and `layers` its `GridLayers`. After a `reset`, `current_cell` is stale so
+    /// hysteresis is skipped.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn update<Marker: Component, const N: usize>(
+        &mut self,
+        grid: &mut Grid<Marker, N>,
+        grid_entity: Option<Entity>,
+        entity: Entity,
+        position: Vec3,
+        explicit_floor: Option<i32>,
+        layers: GridLayers,
+        current_cell: Option<Mut<GridCell<Marker, N>>>,
+        reset: bool,
+    ) {
+        let floor = match grid.floor_mode() {
+            FloorMode::Explicit => explicit_floor.unwrap_or_default(),
+            _ => grid.world_to_floor(position),
+        };
+        let new_cell = match &current_cell {
+            Some(current_cell) if !reset => {
+                grid.local_to_grid_with_hysteresis(position, current_cell.inner)
+            }
+            _ => grid.local_to_grid(position),
+        };
+        let new_cell = match new_cell {
+            Err(GridError::OutOfBounds(cell)) => match grid.out_of_bounds_policy() {
+                OutOfBoundsPolicy::ClampToEdge => Ok(grid.clamp_cell(cell)),
+                OutOfBoundsPolicy::KeepLastCell => current_cell
+                    .as_ref()
+                    .map(|current_cell| current_cell.inner)
+                    .filter(|&current_cell| grid.contains_cell(current_cell))
+                    .ok_or(GridError::OutOfBounds(cell)),
+                _ => Err(GridError::OutOfBounds(cell)),
+            },
+            result => result,
+        };
+        // Entities in chunks that aren't loaded are suspended until the chunk loads
+        let new_cell = new_cell.and_then(|cell| match grid.is_cell_loaded(cell) {
+            true => Ok(cell),
+            false => Err(GridError::ChunkNotLoaded(grid.cell_to_chunk(cell))),
+        });
+        let overflow = grid.out_of_bounds_policy() == OutOfBoundsPolicy::Overflow;
+        match new_cell {
             Ok(new_cell) => {
+                if overflow {
+                    grid.remove_overflow(entity);
+                }
+                grid.unsuspend(entity);
                 let Some(mut current_cell) = current_cell else {
-                    if grid.insert(entity, new_cell).is_ok() {
-                        commands
+                    if grid
+                        .insert_with_layers(entity, new_cell, floor, layers)
+                        .is_ok()
+                    {
+                        self.commands
                             .entity(entity)
-                            .insert(GridCell::<Marker, N>::new(new_cell));
-                        grid_events.write(GridEvent {
+                            .insert(GridCell::<Marker, N>::new(
+                                new_cell,
+                                grid_entity,
+                                floor,
+                                layers,
+                            ));
+                        self.write(GridEvent {
                             entity,
+                            grid: grid_entity,
                             operation: GridOperation::Insert { to: new_cell },
                         });
+                        self.inserts += 1;
                     }
-                    continue;
+                    return;
                 };
+                // A reset empties the grid, so the entity isn't in its old cell anymore
+                if reset {
+                    let _ = grid.insert_with_layers(entity, new_cell, floor, layers);
+                } else if new_cell != current_cell.inner
+                    || floor != current_cell.floor
+                    || layers != current_cell.layers
+                {
+                    let _ = grid.update_with_layers(
+                        entity,
+                        current_cell.inner,
+                        new_cell,
+                        floor,
+                        layers,
+                    );
+                }
                 if new_cell != current_cell.inner {
-                    let _ = grid.update(entity, current_cell.inner, new_cell);
-                    grid_events.write(GridEvent {
+                    self.write(GridEvent {
                         entity,
+                        grid: grid_entity,
                         operation: GridOperation::Update {
                             from: current_cell.inner,
                             to: new_cell,
                         },
                     });
                     current_cell.inner = new_cell;
+                    self.updates += 1;
                 };
+                if floor != current_cell.floor {
+                    self.write(GridEvent {
+                        entity,
+                        grid: grid_entity,
+                        operation: GridOperation::Floor {
+                            from: current_cell.floor,
+                            to: floor,
+                        },
+                    });
+                    current_cell.floor = floor;
+                }
+                if layers != current_cell.layers {
+                    current_cell.layers = layers;
+                }
             }
             Err(GridError::OutOfBounds(_)) => {
+                grid.unsuspend(entity);
                 if let Some(current_cell) = current_cell {
                     let _ = grid.remove(entity, current_cell.inner);
-                    commands.entity(entity).remove::<GridCell<Marker, N>>();
-                    grid_events.write(GridEvent {
+                    self.remove::<Marker, N>(grid_entity, entity, current_cell.inner);
+                }
+                if overflow {
+                    grid.insert_overflow(entity);
+                }
+            }
+            Err(GridError::ChunkNotLoaded(chunk)) => {
+                if overflow {
+                    grid.remove_overflow(entity);
+                }
+                grid.suspend(entity, chunk);
+                if let Some(current_cell) = current_cell {
+                    let _ = grid.remove(entity, current_cell.inner);
+                    self.remove::<Marker, N>(grid_entity, entity, current_cell.inner);
+                }
+            }
+            // Non-finite positions leave the entity where it is
+            _ => (),
+        };
+    }
+
+    /// Find the cell of a static `entity` of the `Grid` resource at `position`,
+    /// keeping its `GridCell` in sync and writing the matching `GridEvent`s.
+    /// Returns the entry for `Grid::extend_static`, or `None` if the entity is
+    /// outside the grid or suspended in a chunk that isn't loaded.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn update_static<Marker: Component, const N: usize>(
+        &mut self,
+        grid: &mut Grid<Marker, N>,
+        entity: Entity,
+        position: Vec3,
+        explicit_floor: Option<i32>,
+        layers: GridLayers,
+        current_cell: Option<&GridCell<Marker, N>>,
+    ) -> Option<(Entity, UVec2, i32, GridLayers)> {
+        let floor = match grid.floor_mode() {
+            FloorMode::Explicit => explicit_floor.unwrap_or_default(),
+            _ => grid.world_to_floor(position),
+        };
+        let new_cell = match grid.local_to_grid(position) {
+            Err(GridError::OutOfBounds(cell))
+                if grid.out_of_bounds_policy() == OutOfBoundsPolicy::ClampToEdge =>
+            {
+                Ok(grid.clamp_cell(cell))
+            }
+            result => result,
+        }
+        .ok();
+        let new_cell = match new_cell {
+            Some(cell) if !grid.is_cell_loaded(cell) => {
+                grid.suspend(entity, grid.cell_to_chunk(cell));
+                None
+            }
+            new_cell => {
+                grid.unsuspend(entity);
+                new_cell
+            }
+        };
+        match (new_cell, current_cell) {
+            (Some(new_cell), None) => {
+                self.commands
+                    .entity(entity)
+                    .insert(GridCell::<Marker, N>::new(new_cell, None, floor, layers));
+                self.write(GridEvent {
+                    entity,
+                    grid: None,
+                    operation: GridOperation::Insert { to: new_cell },
+                });
+                self.inserts += 1;
+            }
+            (Some(new_cell), Some(current_cell)) => {
+                if new_cell != current_cell.inner
+                    || floor != current_cell.floor()
+                    || layers != current_cell.layers()
+                {
+                    self.commands
+                        .entity(entity)
+                        .insert(GridCell::<Marker, N>::new(new_cell, None, floor, layers));
+                }
+                if new_cell != current_cell.inner {
+                    self.write(GridEvent {
                         entity,
-                        operation: GridOperation::Remove {
+                        grid: None,
+                        operation: GridOperation::Update {
                             from: current_cell.inner,
+                            to: new_cell,
+                        },
+                    });
+                    self.updates += 1;
+                }
+                if floor != current_cell.floor() {
+                    self.write(GridEvent {
+                        entity,
+                        grid: None,
+                        operation: GridOperation::Floor {
+                            from: current_cell.floor(),
+                            to: floor,
                         },
                     });
                 }
             }
-            _ => (),
-        };
+            (None, Some(current_cell)) => {
+                self.remove::<Marker, N>(None, entity, current_cell.inner)
+            }
+            (None, None) => (),
+        }
+        new_cell.map(|new_cell| (entity, new_cell, floor, layers))
+    }
+
+    /// Remove the `GridCell` of an `entity` that was removed from `cell` of
+    /// a grid and write the matching `GridEvent`.
+    pub(crate) fn remove<Marker: Component, const N: usize>(
+        &mut self,
+        grid_entity: Option<Entity>,
+        entity: Entity,
+        cell: UVec2,
+    ) {
+        self.commands.entity(entity).remove::<GridCell<Marker, N>>();
+        self.write(GridEvent {
+            entity,
+            grid: grid_entity,
+            operation: GridOperation::Remove { from: cell },
+        });
+        self.removes += 1;
     }
 }
@@ -0,0 +1,263 @@
+use bevy::{
+    ecs::{
+        component::Component,
+        event::EventWriter,
+        system::{Query, ResMut},
+    },
+    math::{IVec2, UVec2},
+};
+use rustc_hash::FxHashSet;
+
+use crate::{
+    component::ChunkAnchor,
+    error::GridError,
+    event::{ChunkEvent, ChunkOperation},
+    position::GridPosition,
+    resource::Grid,
+};
+
+pub(crate) fn update_grid_chunks<Marker: Component, const N: usize, P: GridPosition>(
+    mut grid: ResMut<Grid<Marker, N>>,
+    anchors: Query<(&ChunkAnchor, &P)>,
+    mut chunk_events: EventWriter<ChunkEvent<Marker, N>>,
+) {
+    let Some(size) = grid.chunk_size() else {
+        return;
+    };
+    let last_chunk = grid.dimensions().saturating_sub(UVec2::ONE) / size;
+    let mut loaded = FxHashSet::default();
+    for (anchor, position) in &anchors {
+        // Anchors outside the grid still load the chunks on its edge within range
+        let cell = match grid.world_to_grid(position.grid_position()) {
+            Ok(cell) => cell.as_ivec2(),
+            Err(GridError::OutOfBounds(cell)) => cell,
+            Err(_) => continue,
+        };
+        let chunk = cell.div_euclid(IVec2::splat(size as i32));
+        let radius = anchor.radius as i32;
+        let min = (chunk - radius).max(IVec2::ZERO);
+        let max = (chunk + radius).min(last_chunk.as_ivec2());
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                loaded.insert(UVec2::new(x as u32, y as u32));
+            }
+        }
+    }
+
+    let mut deactivated: Vec<_> = grid
+        .iter_loaded_chunks()
+        .filter(|chunk| !loaded.contains(chunk))
+        .collect();
+    let mut activated: Vec<_> = loaded
+        .iter()
+        .copied()
+        .filter(|&chunk| !grid.is_chunk_loaded(chunk))
+        .collect();
+    if deactivated.is_empty() && activated.is_empty() {
+        return;
+    }
+    deactivated.sort_unstable_by_key(|chunk| (chunk.y, chunk.x));
+    activated.sort_unstable_by_key(|chunk| (chunk.y, chunk.x));
+    chunk_events.write_batch(
+        deactivated
+            .into_iter()
+            .map(|chunk| ChunkEvent::new(chunk, ChunkOperation::Deactivate)),
+    );
+    chunk_events.write_batch(
+        activated
+            .into_iter()
+            .map(|chunk| ChunkEvent::new(chunk, ChunkOperation::Activate)),
+    );
+    grid.set_loaded_chunks(loaded);
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{app::App, math::Vec2, transform::components::Transform};
+
+    use crate::{
+        component::{GridCell, GridStatic},
+        event::{GridEvent, GridOperation},
+        plugin::UniformGrid2dPlugin,
+        test_util::drain,
+    };
+
+    use super::*;
+
+    #[derive(Component)]
+    struct TestMarker;
+
+    fn drain_chunks(app: &mut App) -> Vec<(UVec2, ChunkOperation)> {
+        drain::<ChunkEvent<TestMarker>>(app)
+            .into_iter()
+            .map(|event| (event.chunk, event.operation))
+            .collect()
+    }
+
+    #[test]
+    fn test_anchor_crossing_chunk_border() {
+        let mut app = App::new();
+        app.add_plugins(
+            UniformGrid2dPlugin::<TestMarker>::default()
+                .dimensions(UVec2::new(16, 4))
+                .spacing(Vec2::splat(10.))
+                .chunks(4)
+                .journal(true),
+        );
+        // One entity in each of the first three chunks, and a static one in the second
+        let [first, second, third] = [5., 45., 85.].map(|x| {
+            app.world_mut()
+                .spawn((TestMarker, Transform::from_xyz(x, 5., 0.)))
+                .id()
+        });
+        let wall = app
+            .world_mut()
+            .spawn((TestMarker, GridStatic, Transform::from_xyz(55., 5., 0.)))
+            .id();
+        let anchor = app
+            .world_mut()
+            .spawn((ChunkAnchor::new(1), Transform::from_xyz(5., 5., 0.)))
+            .id();
+        app.update();
+
+        let chunk_event = |x, operation| (UVec2::new(x, 0), operation);
+        let grid_event = |entity, operation| GridEvent {
+            entity,
+            grid: None,
+            operation,
+        };
+        assert_eq!(
+            drain_chunks(&mut app),
+            [
+                chunk_event(0, ChunkOperation::Activate),
+                chunk_event(1, ChunkOperation::Activate),
+            ]
+        );
+        let mut events = drain::<GridEvent>(&mut app);
+        events.sort_by_key(|event| event.entity);
+        assert_eq!(
+            events,
+            [
+                grid_event(
+                    first,
+                    GridOperation::Insert {
+                        to: UVec2::new(0, 0)
+                    }
+                ),
+                grid_event(
+                    second,
+                    GridOperation::Insert {
+                        to: UVec2::new(4, 0)
+                    }
+                ),
+                grid_event(
+                    wall,
+                    GridOperation::Insert {
+                        to: UVec2::new(5, 0)
+                    }
+                ),
+            ]
+        );
+        let grid = app.world().resource::<Grid<TestMarker>>();
+        assert_eq!(
+            grid.iter_suspended(UVec2::new(2, 0)).collect::<Vec<_>>(),
+            [third]
+        );
+
+        // Crossing into the second chunk only loads the third one
+        let move_anchor = |app: &mut App, x| {
+            app.world_mut()
+                .get_mut::<Transform>(anchor)
+                .unwrap()
+                .translation
+                .x = x;
+            app.world_mut()
+                .resource_mut::<Grid<TestMarker>>()
+                .checkpoint();
+            app.update();
+        };
+        move_anchor(&mut app, 45.);
+        assert_eq!(
+            drain_chunks(&mut app),
+            [chunk_event(2, ChunkOperation::Activate)]
+        );
+        assert_eq!(
+            drain::<GridEvent>(&mut app),
+            [grid_event(
+                third,
+                GridOperation::Insert {
+                    to: UVec2::new(8, 0)
+                }
+            )]
+        );
+        let mut grid = app.world_mut().resource_mut::<Grid<TestMarker>>();
+        assert!(grid.iter_suspended(UVec2::new(2, 0)).next().is_none());
+        let delta = grid.checkpoint();
+        assert_eq!(delta.cells, [(UVec2::new(8, 0), vec![])]);
+
+        // Crossing into the third chunk unloads the first one and leaves the others be
+        move_anchor(&mut app, 85.);
+        assert_eq!(
+            drain_chunks(&mut app),
+            [
+                chunk_event(0, ChunkOperation::Deactivate),
+                chunk_event(3, ChunkOperation::Activate),
+            ]
+        );
+        assert_eq!(
+            drain::<GridEvent>(&mut app),
+            [grid_event(
+                first,
+                GridOperation::Remove {
+                    from: UVec2::new(0, 0)
+                }
+            )]
+        );
+        assert!(app.world().get::<GridCell<TestMarker>>(first).is_none());
+        let mut grid = app.world_mut().resource_mut::<Grid<TestMarker>>();
+        assert_eq!(
+            grid.iter_suspended(UVec2::new(0, 0)).collect::<Vec<_>>(),
+            [first]
+        );
+        let mut entities: Vec<_> = grid.iter().collect();
+        entities.sort_unstable();
+        assert_eq!(entities, [second, third, wall]);
+        let delta = grid.checkpoint();
+        assert_eq!(delta.cells, [(UVec2::new(0, 0), vec![(first, 0)])]);
+
+        // Nothing happens while the anchor stays in its chunk
+        app.update();
+        assert!(drain_chunks(&mut app).is_empty());
+        assert!(drain::<GridEvent>(&mut app).is_empty());
+    }
+
+    #[test]
+    fn test_chunk_events_per_grid() {
+        #[derive(Component)]
+        struct OtherMarker;
+
+        let mut app = App::new();
+        app.add_plugins((
+            UniformGrid2dPlugin::<TestMarker>::default()
+                .dimensions(UVec2::new(16, 4))
+                .spacing(Vec2::splat(10.))
+                .chunks(4),
+            UniformGrid2dPlugin::<OtherMarker>::default()
+                .dimensions(UVec2::new(16, 4))
+                .spacing(Vec2::splat(10.))
+                .chunks(8),
+        ));
+        app.world_mut()
+            .spawn((ChunkAnchor::new(0), Transform::from_xyz(5., 5., 0.)));
+        app.update();
+
+        // Each grid only sees the chunks it loaded itself
+        assert_eq!(
+            drain_chunks(&mut app),
+            [(UVec2::ZERO, ChunkOperation::Activate)]
+        );
+        let events = drain::<ChunkEvent<OtherMarker>>(&mut app);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].chunk, UVec2::ZERO);
+    }
+}
@@ -0,0 +1,422 @@
+use bevy::{
+    ecs::{
+        change_detection::DetectChanges,
+        component::Component,
+        entity::Entity,
+        event::{EventReader, EventWriter},
+        query::{Changed, With, Without},
+        removal_detection::RemovedComponents,
+        system::{Commands, Query, ResMut},
+        world::Ref,
+    },
+    transform::components::GlobalTransform,
+};
+use rustc_hash::FxHashSet;
+
+use crate::{
+    component::{GridCell, GridFloor, GridLayers, InGrid},
+    event::{GridEvent, TransformGridEvent},
+    position::GridPosition,
+    resource::Grid,
+    system::GridUpdater,
+};
+
+/// Routes every entity with an `InGrid` relationship to the `Grid<Marker, N>`
+/// component on its target entity. Member positions are relative to the grid's
+/// entity, e.g. the `Transform` of its children. Grids on entities with a
+/// `GlobalTransform` follow it, which only changes how cells map to world space:
+/// members moving along with the grid stay in their cells.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn update_grid_components<Marker: Component, const N: usize, P: GridPosition>(
+    mut commands: Commands,
+    mut grids: Query<(Entity, &mut Grid<Marker, N>)>,
+    moved_grids: Query<
+        (Entity, &GlobalTransform),
+        (With<Grid<Marker, N>>, Changed<GlobalTransform>),
+    >,
+    mut resource_grid: Option<ResMut<Grid<Marker, N>>>,
+    mut members: Query<
+        (
+            Entity,
+            Ref<P>,
+            Option<Ref<GridFloor>>,
+            Option<Ref<GridLayers>>,
+            Ref<InGrid>,
+            Option<&mut GridCell<Marker, N>>,
+        ),
+        With<Marker>,
+    >,
+    former_members: Query<&GridCell<Marker, N>, Without<InGrid>>,
+    mut removed_members: RemovedComponents<InGrid>,
+    mut grid_events: EventWriter<GridEvent>,
+    mut transform_grid_events: EventReader<TransformGridEvent<Marker, N>>,
+) {
+    for (grid_entity, global_transform) in &moved_grids {
+        if let Ok((_, mut grid)) = grids.get_mut(grid_entity) {
+            let frame = grid.plane().project_affine(global_transform.affine());
+            grid.set_frame(frame);
+        }
+    }
+
+    let mut reset_grids = FxHashSet::default();
+    // Grids whose configuration was edited through reflection, e.g. in an inspector
+    for (grid_entity, mut grid) in &mut grids {
+        if grid.is_config_stale() {
+            grid.sync_config();
+            reset_grids.insert(grid_entity);
+        }
+    }
+    for event in transform_grid_events.read() {
+        let Some(grid_entity) = event.grid else {
+            continue;
+        };
+        let Ok((_, mut grid)) = grids.get_mut(grid_entity) else {
+            continue;
+        };
+        if let Some(dimensions) = event.dimensions {
+            grid.set_dimensions(dimensions);
+        };
+        if let Some(spacing) = event.spacing {
+            grid.set_spacing(spacing);
+        };
+        if let Some(anchor) = event.anchor {
+            grid.set_anchor(anchor);
+        };
+        if let Some(transform) = event.transform {
+            grid.set_transform(transform);
+        };
+        reset_grids.insert(grid_entity);
+    }
+    for &grid_entity in &reset_grids {
+        if let Ok((_, mut grid)) = grids.get_mut(grid_entity) {
+            grid.reset();
+        }
+    }
+
+    let deterministic = grids.iter().any(|(_, grid)| grid.deterministic())
+        || resource_grid
+            .as_deref()
+            .is_some_and(|grid| grid.deterministic());
+    let mut updater =
+        GridUpdater::new(&mut commands, &mut grid_events).with_sorted_events(deterministic);
+    // Entities that left their grid, either directly or because the grid was despawned
+    for entity in removed_members.read() {
+        let Ok(cell) = former_members.get(entity) else {
+            continue;
+        };
+        let Some(grid_entity) = cell.grid() else {
+            continue;
+        };
+        if let Ok((_, mut grid)) = grids.get_mut(grid_entity) {
+            let _ = grid.remove(entity, cell.inner);
+            grid.remove_overflow(entity);
+        }
+        updater.remove::<Marker, N>(Some(grid_entity), entity, cell.inner);
+    }
+
+    for (entity, position, floor, layers, in_grid, current_cell) in &mut members {
+        let grid_entity = in_grid.0;
+        let reset = reset_grids.contains(&grid_entity);
+        if !reset
+            && !position.is_changed()
+            && !floor.as_ref().is_some_and(DetectChanges::is_changed)
+            && !layers.as_ref().is_some_and(DetectChanges::is_changed)
+            && !in_grid.is_changed()
+        {
+            continue;
+        }
+        // Entities that moved to another grid are removed from the old one first
+        let current_cell = match current_cell {
+            Some(cell) if cell.grid() != Some(grid_entity) => {
+                match cell.grid() {
+                    Some(old_grid_entity) => {
+                        if let Ok((_, mut old_grid)) = grids.get_mut(old_grid_entity) {
+                            let _ = old_grid.remove(entity, cell.inner);
+                            old_grid.remove_overflow(entity);
+                        }
+                    }
+                    None => {
+                        if let Some(resource_grid) = resource_grid.as_deref_mut() {
+                            let _ = resource_grid.remove(entity, cell.inner);
+                            resource_grid.remove_overflow(entity);
+                        }
+                    }
+                }
+                updater.remove::<Marker, N>(cell.grid(), entity, cell.inner);
+                None
+            }
+            current_cell => current_cell,
+        };
+        let Ok((_, mut grid)) = grids.get_mut(grid_entity) else {
+            continue;
+        };
+        updater.update(
+            &mut grid,
+            Some(grid_entity),
+            entity,
+            position.grid_position(),
+            floor.map(|floor| floor.0),
+            layers.map(|layers| *layers).unwrap_or_default(),
+            current_cell,
+            reset,
+        );
+    }
+    updater.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        app::App,
+        ecs::hierarchy::ChildOf,
+        math::{Quat, UVec2, Vec2},
+        transform::{TransformPlugin, components::Transform},
+    };
+
+    use crate::{event::GridOperation, plugin::UniformGrid2dPlugin, test_util::drain};
+
+    use super::*;
+
+    #[derive(Component)]
+    struct TestMarker;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(
+            UniformGrid2dPlugin::<TestMarker>::default()
+                .dimensions(UVec2::new(20, 20))
+                .spacing(Vec2::splat(10.)),
+        );
+        app
+    }
+
+    fn spawn_grid(app: &mut App) -> Entity {
+        let grid = Grid::<TestMarker>::default()
+            .with_dimensions(UVec2::new(4, 4))
+            .with_spacing(Vec2::splat(100.));
+        app.world_mut().spawn(grid).id()
+    }
+
+    fn cell(app: &App, entity: Entity) -> Option<(UVec2, Option<Entity>)> {
+        app.world()
+            .get::<GridCell<TestMarker>>(entity)
+            .map(|cell| (cell.inner, cell.grid()))
+    }
+
+    #[test]
+    fn test_routing() {
+        let mut app = app();
+        let grid_entity = spawn_grid(&mut app);
+        let member = app
+            .world_mut()
+            .spawn((
+                TestMarker,
+                Transform::from_xyz(155., 55., 0.),
+                InGrid(grid_entity),
+            ))
+            .id();
+        let other = app
+            .world_mut()
+            .spawn((TestMarker, Transform::from_xyz(15., 5., 0.)))
+            .id();
+        app.update();
+
+        assert_eq!(
+            cell(&app, member),
+            Some((UVec2::new(1, 0), Some(grid_entity)))
+        );
+        assert_eq!(cell(&app, other), Some((UVec2::new(1, 0), None)));
+        let grid = app.world().get::<Grid<TestMarker>>(grid_entity).unwrap();
+        assert_eq!(grid.iter().collect::<Vec<_>>(), vec![member]);
+        let resource_grid = app.world().resource::<Grid<TestMarker>>();
+        assert_eq!(resource_grid.iter().collect::<Vec<_>>(), vec![other]);
+        let events = drain::<GridEvent>(&mut app);
+        assert!(events.contains(&GridEvent {
+            entity: member,
+            grid: Some(grid_entity),
+            operation: GridOperation::Insert {
+                to: UVec2::new(1, 0)
+            },
+        }));
+    }
+
+    #[test]
+    fn test_move_between_grids() {
+        let mut app = app();
+        let first = spawn_grid(&mut app);
+        let second = spawn_grid(&mut app);
+        let member = app
+            .world_mut()
+            .spawn((
+                TestMarker,
+                Transform::from_xyz(155., 55., 0.),
+                InGrid(first),
+            ))
+            .id();
+        app.update();
+        drain::<GridEvent>(&mut app);
+
+        app.world_mut().entity_mut(member).insert(InGrid(second));
+        app.update();
+
+        assert_eq!(cell(&app, member), Some((UVec2::new(1, 0), Some(second))));
+        let grid = app.world().get::<Grid<TestMarker>>(first).unwrap();
+        assert_eq!(grid.iter().count(), 0);
+        let grid = app.world().get::<Grid<TestMarker>>(second).unwrap();
+        assert_eq!(grid.iter().collect::<Vec<_>>(), vec![member]);
+        assert_eq!(
+            drain::<GridEvent>(&mut app),
+            vec![
+                GridEvent {
+                    entity: member,
+                    grid: Some(first),
+                    operation: GridOperation::Remove {
+                        from: UVec2::new(1, 0)
+                    },
+                },
+                GridEvent {
+                    entity: member,
+                    grid: Some(second),
+                    operation: GridOperation::Insert {
+                        to: UVec2::new(1, 0)
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_remove_in_grid() {
+        let mut app = app();
+        let grid_entity = spawn_grid(&mut app);
+        let member = app
+            .world_mut()
+            .spawn((
+                TestMarker,
+                Transform::from_xyz(155., 55., 0.),
+                InGrid(grid_entity),
+            ))
+            .id();
+        app.update();
+        drain::<GridEvent>(&mut app);
+
+        // The entity falls back to the resource grid without moving
+        app.world_mut().entity_mut(member).remove::<InGrid>();
+        app.update();
+
+        assert_eq!(cell(&app, member), Some((UVec2::new(15, 5), None)));
+        let grid = app.world().get::<Grid<TestMarker>>(grid_entity).unwrap();
+        assert_eq!(grid.iter().count(), 0);
+        let resource_grid = app.world().resource::<Grid<TestMarker>>();
+        assert_eq!(resource_grid.iter().collect::<Vec<_>>(), vec![member]);
+        assert_eq!(
+            drain::<GridEvent>(&mut app),
+            vec![
+                GridEvent {
+                    entity: member,
+                    grid: Some(grid_entity),
+                    operation: GridOperation::Remove {
+                        from: UVec2::new(1, 0)
+                    },
+                },
+                GridEvent {
+                    entity: member,
+                    grid: None,
+                    operation: GridOperation::Insert {
+                        to: UVec2::new(15, 5)
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_move_grid_entity() {
+        let mut app = app();
+        app.add_plugins(TransformPlugin);
+        let grid = Grid::<TestMarker>::default()
+            .with_dimensions(UVec2::new(4, 4))
+            .with_spacing(Vec2::splat(100.))
+            .with_journal(true);
+        let grid_entity = app.world_mut().spawn((grid, Transform::default())).id();
+        let member = app
+            .world_mut()
+            .spawn((
+                TestMarker,
+                Transform::from_xyz(155., 55., 0.),
+                InGrid(grid_entity),
+                ChildOf(grid_entity),
+            ))
+            .id();
+        app.update();
+        app.update();
+        assert_eq!(
+            cell(&app, member),
+            Some((UVec2::new(1, 0), Some(grid_entity)))
+        );
+        drain::<GridEvent>(&mut app);
+        let mut grid = app
+            .world_mut()
+            .get_mut::<Grid<TestMarker>>(grid_entity)
+            .unwrap();
+        grid.checkpoint();
+
+        // Moving and rotating the grid carries its members along with it
+        *app.world_mut().get_mut::<Transform>(grid_entity).unwrap() =
+            Transform::from_xyz(500., 300., 0.)
+                .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2));
+        app.update();
+        app.update();
+
+        assert!(drain::<GridEvent>(&mut app).is_empty());
+        assert_eq!(
+            cell(&app, member),
+            Some((UVec2::new(1, 0), Some(grid_entity)))
+        );
+        let mut grid = app
+            .world_mut()
+            .get_mut::<Grid<TestMarker>>(grid_entity)
+            .unwrap();
+        assert!(grid.checkpoint().is_empty());
+        assert!(
+            grid.grid_to_world(UVec2::new(1, 0))
+                .abs_diff_eq(Vec2::new(450., 450.), 1e-3)
+        );
+    }
+
+    #[test]
+    fn test_despawn_grid() {
+        let mut app = app();
+        let grid_entity = spawn_grid(&mut app);
+        let members: Vec<Entity> = [55., 155.]
+            .into_iter()
+            .map(|x| {
+                app.world_mut()
+                    .spawn((
+                        TestMarker,
+                        Transform::from_xyz(x, 55., 0.),
+                        InGrid(grid_entity),
+                    ))
+                    .id()
+            })
+            .collect();
+        app.update();
+        drain::<GridEvent>(&mut app);
+
+        app.world_mut().despawn(grid_entity);
+        app.update();
+
+        let events = drain::<GridEvent>(&mut app);
+        for (&member, x) in members.iter().zip([0, 1]) {
+            assert!(app.world().get::<InGrid>(member).is_none());
+            assert!(events.contains(&GridEvent {
+                entity: member,
+                grid: Some(grid_entity),
+                operation: GridOperation::Remove {
+                    from: UVec2::new(x, 0)
+                },
+            }));
+            assert_eq!(cell(&app, member).map(|(_, grid)| grid), Some(None));
+        }
+    }
+}
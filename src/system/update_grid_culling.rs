@@ -0,0 +1,178 @@
+use bevy::{
+    ecs::{
+        change_detection::DetectChangesMut,
+        component::Component,
+        entity::Entity,
+        event::EventReader,
+        query::With,
+        system::{Query, Res, ResMut},
+    },
+    render::view::Visibility,
+};
+
+use crate::{
+    event::{GridEvent, GridOperation},
+    resource::{CameraViews, Grid, GridCulling, cells_difference, contains_cell},
+};
+
+pub(crate) fn update_grid_culling<Marker: Component, const N: usize>(
+    grid: Res<Grid<Marker, N>>,
+    mut culling: ResMut<GridCulling<Marker, N>>,
+    camera_views: Option<Res<CameraViews>>,
+    mut grid_events: EventReader<GridEvent>,
+    mut visibilities: Query<&mut Visibility, With<Marker>>,
+) {
+    let mut set_visible = |entity: Entity, visible: bool| {
+        if let Ok(mut visibility) = visibilities.get_mut(entity) {
+            visibility.set_if_neq(match visible {
+                true => Visibility::Inherited,
+                false => Visibility::Hidden,
+            });
+        }
+    };
+
+    let previous = culling.visible_cells();
+    let visible = camera_views
+        .and_then(|camera_views| camera_views.visible_rect(grid.plane()))
+        .map(|(view, _)| culling.cells_in_view(&grid, view));
+    if visible != previous {
+        match (previous, visible) {
+            (_, None) => {
+                for entity in grid.iter() {
+                    set_visible(entity, true);
+                }
+            }
+            (None, Some(visible)) => {
+                let cells: Vec<_> = grid.iter_occupancy().map(|(cell, _)| cell).collect();
+                for cell in cells {
+                    let in_view = contains_cell(visible, cell);
+                    for entity in grid.get(cell) {
+                        set_visible(entity, in_view);
+                    }
+                }
+            }
+            (Some(previous), Some(visible)) => {
+                for cell in cells_difference(previous, visible) {
+                    for entity in grid.get(cell) {
+                        set_visible(entity, false);
+                    }
+                }
+                for cell in cells_difference(visible, previous) {
+                    for entity in grid.get(cell) {
+                        set_visible(entity, true);
+                    }
+                }
+            }
+        }
+        culling.set_visible_cells(visible);
+    }
+
+    // Entities that left the grid aren't culled anymore
+    for event in grid_events.read().filter(|event| event.grid.is_none()) {
+        match event.operation {
+            GridOperation::Insert { to } | GridOperation::Update { to, .. } => {
+                set_visible(event.entity, culling.is_cell_visible(to));
+            }
+            GridOperation::Remove { .. } => set_visible(event.entity, true),
+            GridOperation::Floor { .. } => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        app::App,
+        math::{Dir3, Ray3d, UVec2, Vec2, Vec3},
+        transform::components::Transform,
+    };
+
+    use crate::{
+        component::GridCell, plugin::UniformGrid2dPlugin, resource::CameraView, test_util::move_to,
+    };
+
+    use super::*;
+
+    #[derive(Component)]
+    struct TestMarker;
+
+    /// Look straight down at the world-space rectangle from `min` to `max`.
+    fn set_view(app: &mut App, view: Option<(Vec2, Vec2)>) {
+        let views = view.map(|(min, max)| {
+            let ray = |x: f32, y: f32| Ray3d::new(Vec3::new(x, y, 10.), Dir3::NEG_Z);
+            CameraView {
+                corners: [
+                    ray(min.x, min.y),
+                    ray(max.x, min.y),
+                    ray(min.x, max.y),
+                    ray(max.x, max.y),
+                ],
+                viewport_size: Vec2::new(800., 600.),
+            }
+        });
+        app.insert_resource(CameraViews {
+            views: views.into_iter().collect(),
+        });
+    }
+
+    fn visibility(app: &App, entity: Entity) -> Visibility {
+        *app.world().get::<Visibility>(entity).unwrap()
+    }
+
+    #[test]
+    fn test_update_grid_culling() {
+        let mut app = App::new();
+        app.add_plugins(
+            UniformGrid2dPlugin::<TestMarker>::default()
+                .dimensions(UVec2::new(10, 10))
+                .spacing(Vec2::splat(10.))
+                .culling(true),
+        );
+        app.world_mut()
+            .resource_mut::<GridCulling<TestMarker>>()
+            .margin = 0;
+        // Cells (0, 0) through (2, 2) are in view
+        set_view(&mut app, Some((Vec2::ZERO, Vec2::splat(30.))));
+        let mut spawn = |x, y| {
+            app.world_mut()
+                .spawn((
+                    TestMarker,
+                    Transform::from_xyz(x, y, 0.),
+                    Visibility::Inherited,
+                ))
+                .id()
+        };
+        let first = spawn(15., 15.);
+        let second = spawn(55., 55.);
+        let third = spawn(25., 5.);
+        app.update();
+        assert_eq!(visibility(&app, first), Visibility::Inherited);
+        assert_eq!(visibility(&app, second), Visibility::Hidden);
+
+        // Cells (4, 4) through (6, 6) are in view
+        set_view(&mut app, Some((Vec2::splat(40.), Vec2::splat(70.))));
+        app.update();
+        assert_eq!(visibility(&app, first), Visibility::Hidden);
+        assert_eq!(visibility(&app, second), Visibility::Inherited);
+
+        // Entities moving into and out of view follow it
+        move_to(&mut app, first, 45., 45.);
+        move_to(&mut app, second, 95., 95.);
+        app.update();
+        assert_eq!(visibility(&app, first), Visibility::Inherited);
+        assert_eq!(visibility(&app, second), Visibility::Hidden);
+
+        // Entities that leave the grid aren't culled anymore
+        move_to(&mut app, second, 150., 150.);
+        app.update();
+        assert!(app.world().get::<GridCell<TestMarker>>(second).is_none());
+        assert_eq!(visibility(&app, second), Visibility::Inherited);
+
+        // Without a view every entity is visible
+        assert_eq!(visibility(&app, third), Visibility::Hidden);
+        set_view(&mut app, None);
+        app.update();
+        assert_eq!(visibility(&app, third), Visibility::Inherited);
+        assert_eq!(visibility(&app, first), Visibility::Inherited);
+    }
+}
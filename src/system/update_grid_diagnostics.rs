@@ -0,0 +1,98 @@
+use bevy::{
+    diagnostic::Diagnostics,
+    ecs::{component::Component, system::Res},
+};
+
+use crate::resource::{Grid, GridDiagnostics};
+
+pub(crate) fn update_grid_diagnostics<Marker: Component, const N: usize>(
+    mut diagnostics: Diagnostics,
+    grid: Res<Grid<Marker, N>>,
+    grid_diagnostics: Res<GridDiagnostics<Marker, N>>,
+) {
+    let (mut entities, mut occupied_cells, mut max_per_cell, mut spilled) = (0, 0, 0, 0);
+    for (_, count) in grid.iter_occupancy() {
+        entities += count;
+        occupied_cells += 1;
+        max_per_cell = max_per_cell.max(count);
+    }
+    // Static entities are stored apart from the cells, so they can't spill
+    for (_, count) in grid.iter_dynamic_occupancy() {
+        if count > N {
+            spilled += count;
+        }
+    }
+    let avg_per_cell = if occupied_cells > 0 {
+        entities as f64 / occupied_cells as f64
+    } else {
+        0.
+    };
+
+    diagnostics.add_measurement(grid_diagnostics.entities(), || entities as f64);
+    diagnostics.add_measurement(grid_diagnostics.occupied_cells(), || occupied_cells as f64);
+    diagnostics.add_measurement(grid_diagnostics.max_per_cell(), || max_per_cell as f64);
+    diagnostics.add_measurement(grid_diagnostics.avg_per_cell(), || avg_per_cell);
+    diagnostics.add_measurement(grid_diagnostics.spilled(), || spilled as f64);
+    diagnostics.add_measurement(grid_diagnostics.inserts(), || {
+        grid_diagnostics.insert_count as f64
+    });
+    diagnostics.add_measurement(grid_diagnostics.updates(), || {
+        grid_diagnostics.update_count as f64
+    });
+    diagnostics.add_measurement(grid_diagnostics.removes(), || {
+        grid_diagnostics.remove_count as f64
+    });
+    diagnostics.add_measurement(grid_diagnostics.update_time(), || {
+        grid_diagnostics.elapsed.as_secs_f64() * 1000.
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        app::App,
+        diagnostic::DiagnosticsStore,
+        math::{UVec2, Vec2},
+        transform::components::Transform,
+    };
+
+    use crate::{component::GridStatic, plugin::UniformGrid2dPlugin};
+
+    use super::*;
+
+    #[derive(Component)]
+    struct TestMarker;
+
+    #[test]
+    fn test_update_grid_diagnostics() {
+        let mut app = App::new();
+        app.add_plugins(
+            UniformGrid2dPlugin::<TestMarker, 2>::default()
+                .dimensions(UVec2::new(10, 10))
+                .spacing(Vec2::splat(10.))
+                .diagnostics(true),
+        );
+        // Three entities spill out of a cell with room for two, one doesn't
+        for x in [1., 2., 3., 35.] {
+            app.world_mut()
+                .spawn((TestMarker, Transform::from_xyz(x, 5., 0.)));
+        }
+        // Statics are stored apart, so they don't spill the other cell
+        for x in [36., 37.] {
+            app.world_mut()
+                .spawn((TestMarker, GridStatic, Transform::from_xyz(x, 5., 0.)));
+        }
+        app.update();
+
+        let diagnostics = GridDiagnostics::<TestMarker, 2>::default();
+        let store = app.world().resource::<DiagnosticsStore>();
+        let value = |path| store.get(path).and_then(|diagnostic| diagnostic.value());
+        assert_eq!(value(diagnostics.entities()), Some(6.));
+        assert_eq!(value(diagnostics.occupied_cells()), Some(2.));
+        assert_eq!(value(diagnostics.max_per_cell()), Some(3.));
+        assert_eq!(value(diagnostics.avg_per_cell()), Some(3.));
+        assert_eq!(value(diagnostics.spilled()), Some(3.));
+        assert_eq!(value(diagnostics.inserts()), Some(6.));
+        assert_eq!(value(diagnostics.removes()), Some(0.));
+    }
+}
@@ -0,0 +1,89 @@
+use bevy::ecs::{
+    change_detection::DetectChanges,
+    component::Component,
+    entity::Entity,
+    event::EventWriter,
+    query::With,
+    system::{Commands, Query, ResMut},
+    world::Ref,
+};
+
+use crate::{
+    component::HexCell,
+    error::GridError,
+    event::{HexGridEvent, HexGridOperation},
+    position::GridPosition,
+    resource::HexGrid,
+};
+
+/// Moves every entity with the `Marker` component between the hexes of the
+/// `HexGrid<Marker, N>`, keeping their `HexCell` in sync and writing a
+/// `HexGridEvent` for every change. Entities outside the grid are removed.
+pub(crate) fn update_hex_grid<Marker: Component, const N: usize, P: GridPosition>(
+    mut commands: Commands,
+    mut grid: ResMut<HexGrid<Marker, N>>,
+    mut grid_elements: Query<(Entity, Ref<P>, Option<&mut HexCell<Marker, N>>), With<Marker>>,
+    mut grid_events: EventWriter<HexGridEvent>,
+) {
+    // The configuration was edited through reflection, e.g. in an inspector, so
+    // every entity is placed again
+    let reset = grid.is_config_stale();
+    if reset {
+        grid.sync_config();
+        grid.reset();
+    }
+    for (entity, position, current_hex) in &mut grid_elements {
+        if !reset && !position.is_changed() {
+            continue;
+        }
+        match grid.world_to_hex(position.grid_position()) {
+            Ok(new_hex) => {
+                let Some(mut current_hex) = current_hex else {
+                    if grid.insert(entity, new_hex).is_ok() {
+                        commands
+                            .entity(entity)
+                            .insert(HexCell::<Marker, N>::new(new_hex));
+                        grid_events.write(HexGridEvent {
+                            entity,
+                            operation: HexGridOperation::Insert { to: new_hex },
+                        });
+                    }
+                    continue;
+                };
+                if reset {
+                    let _ = grid.insert(entity, new_hex);
+                } else if new_hex != current_hex.inner {
+                    let _ = grid.update(entity, current_hex.inner, new_hex);
+                }
+                if new_hex != current_hex.inner {
+                    grid_events.write(HexGridEvent {
+                        entity,
+                        operation: HexGridOperation::Update {
+                            from: current_hex.inner,
+                            to: new_hex,
+                        },
+                    });
+                    current_hex.inner = new_hex;
+                }
+            }
+            Err(GridError::OutOfBounds(_)) => {
+                if let Some(current_hex) = current_hex {
+                    let _ = grid.remove(entity, current_hex.inner);
+                    commands.entity(entity).remove::<HexCell<Marker, N>>();
+                    grid_events.write(HexGridEvent {
+                        entity,
+                        operation: HexGridOperation::Remove {
+                            from: current_hex.inner,
+                        },
+                    });
+                }
+            }
+            // Non-finite positions leave the entity where it is
+            _ => {
+                if let Some(current_hex) = current_hex.filter(|_| reset) {
+                    let _ = grid.insert(entity, current_hex.inner);
+                }
+            }
+        }
+    }
+}
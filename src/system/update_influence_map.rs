@@ -0,0 +1,77 @@
+use bevy::ecs::{
+    component::Component,
+    system::{Query, Res, ResMut},
+};
+
+use crate::{
+    component::{GridCell, InfluenceSource},
+    resource::{Grid, InfluenceMap},
+};
+
+pub(crate) fn update_influence_map<Marker: Component, const N: usize>(
+    grid: Res<Grid<Marker, N>>,
+    mut influence_map: ResMut<InfluenceMap<Marker, N>>,
+    sources: Query<(&InfluenceSource, &GridCell<Marker, N>)>,
+) {
+    influence_map.set_dimensions(grid.dimensions());
+    influence_map.apply_decay();
+    // Members of `Grid<Marker, N>` components are laid out in another grid
+    for (source, cell) in sources.iter().filter(|(_, cell)| cell.grid().is_none()) {
+        influence_map.add_source(source.channel, cell.inner, source.weight, source.radius);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        app::App,
+        math::{UVec2, Vec2},
+        transform::components::Transform,
+    };
+
+    use crate::{
+        component::InGrid,
+        plugin::UniformGrid2dPlugin,
+        resource::{InfluenceFalloff, InfluenceMap},
+    };
+
+    use super::*;
+
+    #[derive(Component)]
+    struct TestMarker;
+
+    #[test]
+    fn test_component_grid_sources() {
+        let mut app = App::new();
+        app.add_plugins(
+            UniformGrid2dPlugin::<TestMarker>::default()
+                .dimensions(UVec2::new(20, 20))
+                .spacing(Vec2::splat(10.))
+                .influence(InfluenceFalloff::Constant, 1.),
+        );
+        let grid = Grid::<TestMarker>::default()
+            .with_dimensions(UVec2::new(4, 4))
+            .with_spacing(Vec2::splat(100.));
+        let grid = app.world_mut().spawn(grid).id();
+        app.world_mut().spawn((
+            TestMarker,
+            InfluenceSource::new(0, 1., 0),
+            Transform::from_xyz(15., 5., 0.),
+        ));
+        // Lies in cell (1, 0) of the component grid, which isn't in the map
+        app.world_mut().spawn((
+            TestMarker,
+            InfluenceSource::new(0, 1., 0),
+            Transform::from_xyz(155., 55., 0.),
+            InGrid(grid),
+        ));
+        app.update();
+        app.update();
+
+        let influence_map = app.world().resource::<InfluenceMap<TestMarker>>();
+        assert_eq!(
+            influence_map.iter(0).collect::<Vec<_>>(),
+            vec![(UVec2::new(1, 0), 1.)]
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use bevy::ecs::{
+    component::Component,
+    entity::{Entity, EntityHashSet},
+    event::{EventReader, EventWriter},
+    query::With,
+    removal_detection::RemovedComponents,
+    system::{Query, Res, ResMut},
+};
+
+use crate::{
+    component::{GridCell, GridObserver},
+    event::{GridEvent, GridOperation, InterestEnter, InterestExit},
+    resource::{Grid, InterestMap, ObserverView},
+};
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn update_interest_map<Marker: Component, const N: usize>(
+    grid: Res<Grid<Marker, N>>,
+    mut interest_map: ResMut<InterestMap<Marker, N>>,
+    observers: Query<(Entity, &GridObserver, Option<&GridCell<Marker, N>>)>,
+    marked: Query<(), With<Marker>>,
+    mut removed_observers: RemovedComponents<GridObserver>,
+    mut removed_entities: RemovedComponents<Marker>,
+    mut grid_events: EventReader<GridEvent>,
+    mut enter_events: EventWriter<InterestEnter>,
+    mut exit_events: EventWriter<InterestExit>,
+) {
+    for observer in removed_observers.read() {
+        if observers.contains(observer) {
+            continue;
+        }
+        if let Some(interest) = interest_map.observers.remove(&observer) {
+            let mut exited: Vec<_> = interest.visible.into_iter().collect();
+            exited.sort_unstable();
+            exit_events.write_batch(
+                exited
+                    .into_iter()
+                    .map(|entity| InterestExit { observer, entity }),
+            );
+        }
+    }
+
+    // Observers that moved or changed radius see the grid from scratch
+    let mut refreshed = EntityHashSet::default();
+    for (observer, grid_observer, cell) in &observers {
+        let view = cell
+            .filter(|cell| cell.grid().is_none())
+            .map(|cell| ObserverView {
+                cell: cell.inner,
+                radius: grid_observer.radius,
+            });
+        let interest = interest_map.observers.entry(observer).or_default();
+        if interest.view == view {
+            continue;
+        }
+        let visible: EntityHashSet = view
+            .map(|view| {
+                grid.iter_radius(view.cell, view.radius)
+                    .filter(|&entity| entity != observer)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut exited: Vec<_> = interest.visible.difference(&visible).copied().collect();
+        let mut entered: Vec<_> = visible.difference(&interest.visible).copied().collect();
+        exited.sort_unstable();
+        entered.sort_unstable();
+        exit_events.write_batch(
+            exited
+                .into_iter()
+                .map(|entity| InterestExit { observer, entity }),
+        );
+        enter_events.write_batch(
+            entered
+                .into_iter()
+                .map(|entity| InterestEnter { observer, entity }),
+        );
+        interest.view = view;
+        interest.visible = visible;
+        refreshed.insert(observer);
+    }
+
+    // Everyone else follows the entities that moved. Events of grids with another
+    // `Marker` are skipped; entities that lost `Marker` are handled below.
+    for event in grid_events
+        .read()
+        .filter(|event| event.grid.is_none() && marked.contains(event.entity))
+    {
+        let entity = event.entity;
+        let cell = match event.operation {
+            GridOperation::Insert { to } | GridOperation::Update { to, .. } => Some(to),
+            GridOperation::Remove { .. } => None,
+            GridOperation::Floor { .. } => continue,
+        };
+        for (&observer, interest) in &mut interest_map.observers {
+            if refreshed.contains(&observer) || observer == entity {
+                continue;
+            }
+            let Some(view) = interest.view else {
+                continue;
+            };
+            let in_view = cell.is_some_and(|cell| view.contains(cell));
+            if in_view && interest.visible.insert(entity) {
+                enter_events.write(InterestEnter { observer, entity });
+            } else if !in_view && interest.visible.remove(&entity) {
+                exit_events.write(InterestExit { observer, entity });
+            }
+        }
+    }
+
+    // Despawned entities don't leave the grid with a `GridEvent`
+    for entity in removed_entities.read() {
+        for (&observer, interest) in &mut interest_map.observers {
+            if interest.visible.remove(&entity) {
+                exit_events.write(InterestExit { observer, entity });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        app::App,
+        math::{UVec2, Vec2},
+        transform::components::Transform,
+    };
+
+    use crate::{
+        plugin::UniformGrid2dPlugin,
+        test_util::{drain, move_to},
+    };
+
+    use super::*;
+
+    #[derive(Component)]
+    struct TestMarker;
+
+    /// The `(observer, entity)` pairs of the `InterestEnter` and `InterestExit`
+    /// events written since the last call, sorted.
+    fn drain_interest(app: &mut App) -> (Vec<(Entity, Entity)>, Vec<(Entity, Entity)>) {
+        let mut entered: Vec<_> = drain::<InterestEnter>(app)
+            .into_iter()
+            .map(|event| (event.observer, event.entity))
+            .collect();
+        let mut exited: Vec<_> = drain::<InterestExit>(app)
+            .into_iter()
+            .map(|event| (event.observer, event.entity))
+            .collect();
+        entered.sort_unstable();
+        exited.sort_unstable();
+        (entered, exited)
+    }
+
+    #[test]
+    fn test_interest_events() {
+        let mut app = App::new();
+        app.add_plugins(
+            UniformGrid2dPlugin::<TestMarker>::default()
+                .dimensions(UVec2::new(10, 10))
+                .spacing(Vec2::splat(10.))
+                .interest(true),
+        );
+        let mut spawn = |x, y| {
+            app.world_mut()
+                .spawn((TestMarker, Transform::from_xyz(x, y, 0.)))
+                .id()
+        };
+        // `observer` sees the cells around (5, 5), `other` only its own cell (4, 4)
+        let observer = spawn(55., 55.);
+        let other = spawn(45., 45.);
+        let near = spawn(65., 55.);
+        let far = spawn(95., 95.);
+        let edge = spawn(95., 55.);
+        app.world_mut()
+            .entity_mut(observer)
+            .insert(GridObserver::new(1));
+        app.world_mut()
+            .entity_mut(other)
+            .insert(GridObserver::new(0));
+        app.update();
+        assert_eq!(
+            drain_interest(&mut app),
+            (vec![(observer, other), (observer, near)], vec![])
+        );
+
+        // Entities moving into and out of view are followed by every observer
+        move_to(&mut app, far, 45., 45.);
+        move_to(&mut app, near, 85., 55.);
+        app.update();
+        assert_eq!(
+            drain_interest(&mut app),
+            (vec![(observer, far), (other, far)], vec![(observer, near)])
+        );
+
+        // An observer that moves sees the grid from scratch
+        move_to(&mut app, observer, 85., 55.);
+        app.update();
+        assert_eq!(
+            drain_interest(&mut app),
+            (
+                vec![(observer, near), (observer, edge)],
+                vec![(observer, other), (observer, far)]
+            )
+        );
+
+        // Despawned entities leave the view without a `GridEvent`
+        app.world_mut().despawn(near);
+        app.update();
+        assert_eq!(drain_interest(&mut app), (vec![], vec![(observer, near)]));
+
+        // Removing `GridObserver` exits everything the observer could see
+        app.world_mut()
+            .entity_mut(observer)
+            .remove::<GridObserver>();
+        app.update();
+        assert_eq!(drain_interest(&mut app), (vec![], vec![(observer, edge)]));
+        let interest_map = app.world().resource::<InterestMap<TestMarker>>();
+        assert!(!interest_map.observers.contains_key(&observer));
+    }
+
+    #[test]
+    fn test_interest_other_marker() {
+        #[derive(Component)]
+        struct OtherMarker;
+
+        let mut app = App::new();
+        app.add_plugins((
+            UniformGrid2dPlugin::<TestMarker>::default()
+                .dimensions(UVec2::new(10, 10))
+                .spacing(Vec2::splat(10.))
+                .interest(true),
+            UniformGrid2dPlugin::<OtherMarker>::default()
+                .dimensions(UVec2::new(10, 10))
+                .spacing(Vec2::splat(10.)),
+        ));
+        let observer = app
+            .world_mut()
+            .spawn((
+                TestMarker,
+                GridObserver::new(1),
+                Transform::from_xyz(55., 55., 0.),
+            ))
+            .id();
+        let other = app
+            .world_mut()
+            .spawn((OtherMarker, Transform::from_xyz(95., 95., 0.)))
+            .id();
+        app.update();
+        drain_interest(&mut app);
+
+        // Entities of the other grid moving next to the observer aren't seen
+        move_to(&mut app, other, 65., 55.);
+        app.update();
+        assert_eq!(drain_interest(&mut app), (vec![], vec![]));
+        let interest_map = app.world().resource::<InterestMap<TestMarker>>();
+        assert!(interest_map.observers[&observer].visible.is_empty());
+    }
+}
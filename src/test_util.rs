@@ -0,0 +1,23 @@
+use bevy::{
+    app::App,
+    ecs::{
+        entity::Entity,
+        event::{Event, Events},
+    },
+    math::Vec2,
+    transform::components::Transform,
+};
+
+/// Take the `E` events written since they were last drained, oldest first.
+pub(crate) fn drain<E: Event>(app: &mut App) -> Vec<E> {
+    app.world_mut()
+        .resource_mut::<Events<E>>()
+        .drain()
+        .collect()
+}
+
+/// Move an `entity` to `(x, y)` on the `XY` plane through its `Transform`.
+pub(crate) fn move_to(app: &mut App, entity: Entity, x: f32, y: f32) {
+    let mut transform = app.world_mut().get_mut::<Transform>(entity).unwrap();
+    transform.translation = Vec2::new(x, y).extend(0.);
+}
@@ -0,0 +1,88 @@
+/// Strip the module path from every type in `type_name`, e.g.
+/// `my_game::Foo<my_game::Bar>` becomes `Foo<Bar>`.
+pub(crate) fn short_type_name(type_name: &str) -> String {
+    let mut short = String::with_capacity(type_name.len());
+    let mut segment_start = 0;
+    for (i, c) in type_name.char_indices() {
+        if matches!(c, '<' | '>' | ',' | ' ' | '(' | ')' | '[' | ']' | '&' | ';') {
+            let segment = &type_name[segment_start..i];
+            short.push_str(segment.rsplit("::").next().unwrap_or(segment));
+            short.push(c);
+            segment_start = i + c.len_utf8();
+        }
+    }
+    let segment = &type_name[segment_start..];
+    short.push_str(segment.rsplit("::").next().unwrap_or(segment));
+    short
+}
+
+/// Implement `TypePath` for a type that is generic over a `Marker` component,
+/// and optionally over the inline capacity `N` of its cells. Markers are plain
+/// components that usually don't implement `TypePath`, so the derived
+/// implementation can't be used.
+macro_rules! impl_marker_type_path {
+    ($ident:ident<Marker $(, $n:ident)?>) => {
+        impl<Marker: ::bevy::ecs::component::Component $(, const $n: usize)?>
+            ::bevy::reflect::TypePath for $ident<Marker $(, $n)?>
+        {
+            fn type_path() -> &'static str {
+                static CELL: ::bevy::reflect::utility::GenericTypePathCell =
+                    ::bevy::reflect::utility::GenericTypePathCell::new();
+                CELL.get_or_insert::<Self, _>(|| {
+                    let params: &[String] = &[
+                        ::std::any::type_name::<Marker>().to_string()
+                        $(, $n.to_string())?
+                    ];
+                    format!(
+                        "{}::{}<{}>",
+                        module_path!(),
+                        stringify!($ident),
+                        params.join(", ")
+                    )
+                })
+            }
+
+            fn short_type_path() -> &'static str {
+                static CELL: ::bevy::reflect::utility::GenericTypePathCell =
+                    ::bevy::reflect::utility::GenericTypePathCell::new();
+                CELL.get_or_insert::<Self, _>(|| {
+                    let params: &[String] = &[
+                        $crate::type_path::short_type_name(::std::any::type_name::<Marker>())
+                        $(, $n.to_string())?
+                    ];
+                    format!("{}<{}>", stringify!($ident), params.join(", "))
+                })
+            }
+
+            fn type_ident() -> Option<&'static str> {
+                Some(stringify!($ident))
+            }
+
+            fn crate_name() -> Option<&'static str> {
+                module_path!().split("::").next()
+            }
+
+            fn module_path() -> Option<&'static str> {
+                Some(module_path!())
+            }
+        }
+    };
+}
+
+pub(crate) use impl_marker_type_path;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_type_name() {
+        assert_eq!(short_type_name("Foo"), "Foo");
+        assert_eq!(short_type_name("my_game::Foo"), "Foo");
+        assert_eq!(
+            short_type_name("my_game::Foo<my_game::units::Bar, 4>"),
+            "Foo<Bar, 4>"
+        );
+        assert_eq!(short_type_name("(a::B, &c::D)"), "(B, &D)");
+    }
+}